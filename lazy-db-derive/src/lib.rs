@@ -0,0 +1,256 @@
+//! > **Derive macros for `lazy-db`**
+//!
+//! Provides `#[derive(LazyObject)]` which generates the `LazyObject` implementation, lazy getters, setters and a `Drop` implementation for a struct.
+//!
+//! ## Field Rules
+//! - Exactly one field must be a `LazyContainer`; it is the container the object is stored in
+//! - An optional `DirtyFields` field makes `store_lazy` only write the fields that were changed through setters
+//! - Every other field must be an `Option<T>` which is stored as `LazyData` at a key of the same name
+//!
+//! ## Attributes
+//! - `#[lazy(rename = "key")]` stores the field at a different key
+//! - `#[lazy(child)]` stores a nested `LazyObject` as a child container instead of `LazyData`
+//! - `#[lazy(skip)]` ignores the field and initialises it with `Default::default()`
+//! - `#[lazy(no_drop)]` (on the struct) doesn't generate the `Drop` implementation;
+//!   `Drop` is a supertrait of `LazyObject`, so it must then be implemented by hand
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Fields, GenericArgument, Ident, LitStr, PathArguments, Type};
+
+#[proc_macro_derive(LazyObject, attributes(lazy))]
+pub fn derive_lazy_object(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(input) {
+        Ok(x) => x.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// How a field of the struct is stored
+enum FieldKind {
+    Container,
+    Dirty,
+    Skip,
+    Data { key: String, inner: Type },
+    Child { key: String, inner: Type },
+}
+
+struct LazyField {
+    ident: Ident,
+    kind: FieldKind,
+}
+
+/// Parsed `#[lazy(...)]` attributes of a field
+#[derive(Default)]
+struct FieldAttrs {
+    rename: Option<String>,
+    child: bool,
+    skip: bool,
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    // Parse struct attributes
+    let mut no_drop = false;
+    for attr in input.attrs.iter().filter(|x| x.path().is_ident("lazy")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("no_drop") { no_drop = true; Ok(()) }
+            else { Err(meta.error("unknown `lazy` struct attribute")) }
+        })?;
+    }
+
+    // Only named structs are supported
+    let fields = match &input.data {
+        Data::Struct(x) => match &x.fields {
+            Fields::Named(x) => &x.named,
+            _ => return Err(syn::Error::new(Span::call_site(), "`LazyObject` can only be derived for structs with named fields")),
+        },
+        _ => return Err(syn::Error::new(Span::call_site(), "`LazyObject` can only be derived for structs")),
+    };
+
+    // Classify the fields
+    let mut lazy_fields = Vec::new();
+    for field in fields {
+        let ident = field.ident.clone().unwrap();
+        let attrs = parse_field_attrs(&field.attrs)?;
+        let key = attrs.rename.unwrap_or_else(|| ident.to_string().trim_start_matches("r#").to_string());
+
+        let kind = if attrs.skip { FieldKind::Skip }
+            else if type_is(&field.ty, "LazyContainer") { FieldKind::Container }
+            else if type_is(&field.ty, "DirtyFields") { FieldKind::Dirty }
+            else if let Some(inner) = option_inner(&field.ty) {
+                if attrs.child { FieldKind::Child { key, inner } }
+                else { FieldKind::Data { key, inner } }
+            } else {
+                return Err(syn::Error::new(field.ty.span(), "fields of a `LazyObject` must be `Option<T>` (or use `#[lazy(skip)]`)"));
+            };
+
+        lazy_fields.push(LazyField { ident, kind });
+    }
+
+    // Find the container and dirty fields
+    let mut containers = lazy_fields.iter().filter(|x| matches!(x.kind, FieldKind::Container));
+    let container = match (containers.next(), containers.next()) {
+        (Some(x), None) => &x.ident,
+        _ => return Err(syn::Error::new(Span::call_site(), "`LazyObject` requires exactly one `LazyContainer` field")),
+    };
+    let dirty = lazy_fields.iter().find(|x| matches!(x.kind, FieldKind::Dirty)).map(|x| &x.ident);
+
+    // Generate the trait implementation
+    let mut store = Vec::new();
+    let mut load = Vec::new();
+    let mut clear = Vec::new();
+    let mut methods = Vec::new();
+
+    for LazyField { ident, kind } in lazy_fields.iter() {
+        match kind {
+            FieldKind::Container => load.push(quote!(#ident: container)),
+            FieldKind::Dirty | FieldKind::Skip => load.push(quote!(#ident: ::std::default::Default::default())),
+            FieldKind::Data { key, inner } => {
+                let setter = format_ident!("set_{}", ident.to_string().trim_start_matches("r#"));
+                let is_dirty = match dirty {
+                    Some(d) => quote!(self.#d.is_dirty(#key)),
+                    None => quote!(true),
+                };
+                let mark = dirty.map(|d| quote!(self.#d.mark(#key);));
+
+                load.push(quote!(#ident: ::std::option::Option::None));
+                store.push(quote! {
+                    if let ::std::option::Option::Some(x) = &self.#ident {
+                        if #is_dirty { self.#container.write(#key, x)?; }
+                    }
+                });
+                clear.push(match dirty {
+                    Some(d) => quote!(if !self.#d.is_dirty(#key) { self.#ident = ::std::option::Option::None; }),
+                    None => quote!(self.#ident = ::std::option::Option::None;),
+                });
+
+                let getter_doc = format!("Lazily loads and caches the `{key}` field");
+                let setter_doc = format!("Sets the `{key}` field (stored on the next `store_lazy`)");
+                methods.push(quote! {
+                    #[doc = #getter_doc]
+                    pub fn #ident(&mut self) -> ::std::result::Result<&#inner, ::lazy_db::LDBError> {
+                        if self.#ident.is_none() {
+                            self.#ident = ::std::option::Option::Some(self.#container.read_data(#key)?.collect::<#inner>()?);
+                        }
+                        ::std::result::Result::Ok(self.#ident.as_ref().unwrap())
+                    }
+
+                    #[doc = #setter_doc]
+                    pub fn #setter(&mut self, value: #inner) {
+                        self.#ident = ::std::option::Option::Some(value);
+                        #mark
+                    }
+                });
+            },
+            FieldKind::Child { key, inner } => {
+                load.push(quote!(#ident: ::std::option::Option::None));
+                store.push(quote! {
+                    if let ::std::option::Option::Some(x) = &self.#ident {
+                        ::lazy_db::LazyObject::store_lazy(x)?;
+                    }
+                });
+                clear.push(quote!(self.#ident = ::std::option::Option::None;));
+
+                let getter_doc = format!("Lazily loads and caches the `{key}` child object");
+                methods.push(quote! {
+                    #[doc = #getter_doc]
+                    pub fn #ident(&mut self) -> ::std::result::Result<&mut #inner, ::lazy_db::LDBError> {
+                        if self.#ident.is_none() {
+                            self.#ident = ::std::option::Option::Some(<#inner as ::lazy_db::LazyObject>::load_lazy(self.#container.child_container(#key)?));
+                        }
+                        ::std::result::Result::Ok(self.#ident.as_mut().unwrap())
+                    }
+                });
+            },
+        }
+    }
+
+    let clear_dirty = dirty.map(|d| quote!(self.#d.clear();));
+    let drop_impl = if no_drop { None } else {
+        Some(quote! {
+            impl #impl_generics ::std::ops::Drop for #name #ty_generics #where_clause {
+                fn drop(&mut self) {
+                    let _ = ::lazy_db::LazyObject::store_lazy(self);
+                }
+            }
+        })
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::lazy_db::LazyObject for #name #ty_generics #where_clause {
+            fn as_container(&self) -> &::lazy_db::LazyContainer {
+                &self.#container
+            }
+
+            fn store_lazy(&self) -> ::std::result::Result<(), ::lazy_db::LDBError> {
+                #(#store)*
+                #clear_dirty
+                ::std::result::Result::Ok(())
+            }
+
+            fn load_lazy(container: ::lazy_db::LazyContainer) -> Self {
+                Self {
+                    #(#load,)*
+                }
+            }
+
+            fn clear_cache(&mut self) {
+                #(#clear)*
+            }
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            #(#methods)*
+        }
+
+        #drop_impl
+    })
+}
+
+fn parse_field_attrs(attrs: &[syn::Attribute]) -> syn::Result<FieldAttrs> {
+    let mut result = FieldAttrs::default();
+    for attr in attrs.iter().filter(|x| x.path().is_ident("lazy")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                result.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("child") {
+                result.child = true;
+            } else if meta.path.is_ident("skip") {
+                result.skip = true;
+            } else {
+                return Err(meta.error("unknown `lazy` field attribute"));
+            } Ok(())
+        })?;
+    }
+    Ok(result)
+}
+
+/// Checks if the last segment of a type's path is the specified name
+fn type_is(ty: &Type, name: &str) -> bool {
+    match ty {
+        Type::Path(x) => x.path.segments.last().map(|x| x.ident == name).unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// Gets the `T` of an `Option<T>`
+fn option_inner(ty: &Type) -> Option<Type> {
+    let segment = match ty {
+        Type::Path(x) if x.qself.is_none() => x.path.segments.last()?,
+        _ => return None,
+    };
+    if segment.ident != "Option" { return None };
+
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => match args.args.first()? {
+            GenericArgument::Type(x) => Some(x.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
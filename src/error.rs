@@ -12,6 +12,16 @@ pub enum LDBError {
     InvalidNumberByteLength(u8, String),
     InvalidMetaVersion(PathBuf),
     IncompatibleVersion(crate::version::Version),
+    DatabaseLocked(PathBuf),
+    ReadOnly(PathBuf),
+    IndexOutOfBounds(usize, usize),
+    InvalidKey(String),
+    InvalidValue(LazyType, String),
+    LinkCycle(PathBuf),
+    LinkDepthExceeded(PathBuf, usize),
+    StillLinked(PathBuf, PathBuf),
+    #[cfg(feature = "serde")]
+    SerdeError(String),
 }
 
 impl fmt::Display for LDBError {
@@ -27,6 +37,16 @@ impl fmt::Display for LDBError {
             InvalidNumberByteLength(x, t) => write!(f, "Invalid byte length '{x}' for number type '{t:?}'"),
             InvalidMetaVersion(p) => write!(f, "Invalid version for `lazy-db` at '{}'", p.to_string_lossy()),
             IncompatibleVersion(v) => write!(f, "Found version '{v}' incompatible with current version '{}'", crate::VERSION),
+            DatabaseLocked(p) => write!(f, "Database lock '{}' is already held", p.to_string_lossy()),
+            ReadOnly(p) => write!(f, "Cannot modify '{}' as it's read-only", p.to_string_lossy()),
+            InvalidKey(k) => write!(f, "Invalid key '{k}' (keys can't be paths or start with '.')"),
+            IndexOutOfBounds(i, len) => write!(f, "Index '{i}' is out of bounds for an array of length '{len}'"),
+            InvalidValue(t, e) => write!(f, "Invalid value for type '{t:?}': {e}"),
+            LinkCycle(p) => write!(f, "Link '{}' is part of a cycle of links", p.to_string_lossy()),
+            LinkDepthExceeded(p, d) => write!(f, "Link '{}' is more than {d} links deep", p.to_string_lossy()),
+            StillLinked(l, p) => write!(f, "Cannot remove '{}' as link '{}' still points to it", p.to_string_lossy(), l.to_string_lossy()),
+            #[cfg(feature = "serde")]
+            SerdeError(e) => write!(f, "Serde Error: {e}"),
         }
     }
 }
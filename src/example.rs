@@ -16,7 +16,7 @@ impl LazyObject for Person {
         };
 
         if let Some(x) = self.age {
-            LazyData::new_u8(self.container.data_writer("name")?, x)?
+            LazyData::new_u8(self.container.data_writer("age")?, x)?
         };
 
         Ok(())
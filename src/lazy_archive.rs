@@ -1,84 +1,194 @@
+use crate::*;
+use std::collections::BTreeMap;
 use std::fs::File;
-use std::io::{self, Read, Write};
-use std::path::{Path, PathBuf};
-use tar::Builder;
-use lz4_flex::frame::{FrameEncoder, FrameDecoder};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+use lz4_flex::frame::FrameDecoder;
+use lz4_flex::block::{compress_prepend_size, decompress_size_prepended};
 use std::fs;
 
-const BUFFER_SIZE: usize = 8192;
+/// The first bytes of an indexed archive
+pub const MAGIC: [u8; 8] = *b"LAZYDBX\x01";
 
-pub fn build_tar(path: impl AsRef<Path>, tar_path: impl AsRef<Path>) -> Result<(), io::Error> {
-    let tar = File::create(tar_path)?;
-    let mut builder = Builder::new(tar);
+/// Where an entry of an indexed archive is stored (`None` is a container)
+pub type IndexEntry = Option<(u64, u64)>;
 
-    recursive_tar_append(&mut builder, path, PathBuf::new())?;
+/// Builds an indexed archive of a directory of a `StorageBackend`
+///
+/// ## Layout
+/// - `MAGIC`
+/// - every `LazyData` file, each compressed as it's own lz4 block
+/// - the index: the amount of entries (`u64`), then for each entry it's kind (`u8`; `0` is data, `1` is a container),
+///   `/`-separated path length (`u32`) and path, and for data the offset and length (`u64`s) of it's block
+/// - the offset of the index (`u64`)
+///
+/// (all numbers are little-endian)
+pub fn build_indexed(backend: &dyn StorageBackend, path: impl AsRef<Path>, mut out: impl Write) -> Result<(), io::Error> {
+    out.write_all(&MAGIC)?;
+    let mut offset = MAGIC.len() as u64;
+    let mut index = Vec::new();
+    recursive_append(backend, &mut out, path.as_ref(), "", &mut offset, &mut index)?;
 
-    builder.finish()?;
-    Ok(())
+    // Write the index and footer
+    let mut bytes = (index.len() as u64).to_le_bytes().to_vec();
+    for (path, entry) in index {
+        bytes.push(entry.is_none() as u8);
+        bytes.extend((path.len() as u32).to_le_bytes());
+        bytes.extend(path.as_bytes());
+        if let Some((offset, len)) = entry {
+            bytes.extend(offset.to_le_bytes());
+            bytes.extend(len.to_le_bytes());
+        }
+    }
+    out.write_all(&bytes)?;
+    out.write_all(&offset.to_le_bytes())?;
+    out.flush()
 }
 
-fn recursive_tar_append(builder: &mut Builder<File>, path: impl AsRef<Path>, tar_path: PathBuf) -> Result<(), io::Error> {
-    for entry in std::fs::read_dir(path)?.filter_map(|x| x.ok()) {
-        let path = entry.path();
-        let file_type = entry.file_type()?;
-        if file_type.is_file() {
-            builder.append_file(
-                tar_path.join(entry.file_name()),
-                &mut File::open(&path)?
-            )?;
-        } else if file_type.is_dir() {
-            recursive_tar_append(builder, path, tar_path.join(entry.file_name()))?;
+fn recursive_append(backend: &dyn StorageBackend, out: &mut impl Write, path: &Path, archive_path: &str, offset: &mut u64, index: &mut Vec<(String, IndexEntry)>) -> Result<(), io::Error> {
+    let mut entries = backend.list(path)?;
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    for (name, kind) in entries {
+        if is_internal(&name) { continue };
+        let path = path.join(&name);
+        let archive_path = if archive_path.is_empty() { name } else { format!("{archive_path}/{name}") };
+
+        match kind {
+            EntryKind::Data => {
+                let data = match backend.open_reader(&path)?.read_to_end() {
+                    Ok(x) => x,
+                    Err(LDBError::IOError(e)) => return Err(e),
+                    Err(e) => return Err(io::Error::other(e.to_string())),
+                };
+                let block = compress_prepend_size(&data);
+                out.write_all(&block)?;
+                index.push((archive_path, Some((*offset, block.len() as u64))));
+                *offset += block.len() as u64;
+            },
+            EntryKind::Container => {
+                index.push((archive_path.clone(), None));
+                recursive_append(backend, out, &path, &archive_path, offset, index)?;
+            },
         }
     };
-    
+
     Ok(())
 }
 
-pub fn unpack_tar(path: impl AsRef<Path>, dir_path: impl AsRef<Path>) -> Result<(), io::Error> {
-    let tar = File::open(path)?;
-    let mut archive = tar::Archive::new(tar);
-    let dir_path = dir_path.as_ref();
-    fs::create_dir_all(dir_path)?;
+/// Checks if an entry is internal state of a database (locks, logs, staged transactions, the link index and temporary files)
+/// rather than part of it
+fn is_internal(name: &str) -> bool {
+    [lazy_lock::LOCK_FILE, lazy_wal::WAL_FILE, lazy_transaction::STAGING_DIR, lazy_transaction::FAILED_DIR, lazy_link::LINK_INDEX].contains(&name)
+        || lazy_data::parse_leftover(name).is_some()
+}
 
-    for entry in archive.entries()? {
-        let mut entry = entry?;
-        entry.unpack_in(dir_path)?;
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Checks if a file is an indexed archive (rather than a compressed tarball)
+pub fn is_indexed(file: &mut File) -> Result<bool, io::Error> {
+    let mut magic = [0u8; MAGIC.len()];
+    file.seek(SeekFrom::Start(0))?;
+    let indexed = file.read_exact(&mut magic).is_ok() && magic == MAGIC;
+    file.seek(SeekFrom::Start(0))?;
+    Ok(indexed)
+}
+
+/// Reads the index of an indexed archive (paths are relative to the archive's root)
+pub fn read_index(file: &mut File) -> Result<BTreeMap<PathBuf, IndexEntry>, io::Error> {
+    if !is_indexed(file)? { return Err(invalid("not an indexed lazy-db archive")) };
+
+    // Read the footer and then the index
+    let end = file.seek(SeekFrom::End(-8))?;
+    let mut offset = [0u8; 8];
+    file.read_exact(&mut offset)?;
+    let offset = u64::from_le_bytes(offset);
+    if offset > end { return Err(invalid("invalid index offset")) };
+    let mut bytes = vec![0u8; (end - offset) as usize];
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut bytes)?;
+
+    let mut bytes = bytes.as_slice();
+    let mut take = |n: usize| -> Result<&[u8], io::Error> {
+        if bytes.len() < n { return Err(invalid("truncated index")) };
+        let (x, rest) = bytes.split_at(n);
+        bytes = rest;
+        Ok(x)
     };
 
-    Ok(())
+    let mut index = BTreeMap::new();
+    let count = u64::from_le_bytes(take(8)?.try_into().unwrap());
+    for _ in 0..count {
+        let is_container = take(1)?[0] == 1;
+        let len = u32::from_le_bytes(take(4)?.try_into().unwrap()) as usize;
+        let path = match std::str::from_utf8(take(len)?) {
+            Ok(x) => PathBuf::from(x),
+            Err(_) => return Err(invalid("invalid path in index")),
+        };
+        if !path.components().all(|x| matches!(x, Component::Normal(_))) { return Err(invalid("invalid path in index")) };
+        let entry = if is_container { None } else {
+            let offset = u64::from_le_bytes(take(8)?.try_into().unwrap());
+            let len = u64::from_le_bytes(take(8)?.try_into().unwrap());
+            Some((offset, len))
+        };
+        index.insert(path, entry);
+    }
+
+    Ok(index)
 }
 
-pub fn compress_file(path: impl AsRef<Path>, out_path: impl AsRef<Path>) -> Result<(), io::Error> {
+/// The most an lz4 block can be compressed by
+const MAX_RATIO: u64 = 255;
+
+/// Reads and decompresses a block of an indexed archive
+/// 
+/// Returns an `io::ErrorKind::InvalidData` error if the block isn't within the file or it's size is more than it could decompress to
+pub fn read_block(file: &mut File, offset: u64, len: u64) -> Result<Vec<u8>, io::Error> {
+    let size = file.metadata()?.len();
+    if offset.checked_add(len).is_none_or(|end| end > size) || len < 4 { return Err(invalid("block is outside of the archive")) };
+    let mut block = vec![0u8; len as usize];
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut block)?;
+
+    let decompressed = u32::from_le_bytes(block[..4].try_into().unwrap()) as u64;
+    if decompressed > (len - 4).saturating_mul(MAX_RATIO) { return Err(invalid("block is larger than it could decompress to")) };
+    decompress_size_prepended(&block).map_err(|e| invalid(&e.to_string()))
+}
+
+/// Unpacks an indexed archive into a directory
+pub fn unpack_indexed(path: impl AsRef<Path>, dir_path: impl AsRef<Path>) -> Result<(), io::Error> {
     let mut file = File::open(path)?;
-    let out = File::create(out_path)?;
-    
-    let mut encoder = FrameEncoder::new(out);
-    let mut buffer = [0u8; BUFFER_SIZE]; // 8KB read buffer
-
-    // Read data from input and write compressed output
-    loop {
-        let bytes_read = file.read(&mut buffer)?;
-        if bytes_read == 0 { break };
-        encoder.write_all(&buffer[..bytes_read])?;
+    let dir_path = dir_path.as_ref();
+    fs::create_dir_all(dir_path)?;
+
+    // Parents always come before their children
+    for (path, entry) in read_index(&mut file)? {
+        match entry {
+            Some((offset, len)) => fs::write(dir_path.join(path), read_block(&mut file, offset, len)?)?,
+            None => fs::create_dir_all(dir_path.join(path))?,
+        }
     }
 
-    encoder.finish()?;
     Ok(())
 }
 
-pub fn decompress_file(path: impl AsRef<Path>, out_path: impl AsRef<Path>) -> Result<(), io::Error> {
-    let file = File::open(path)?;
-    let mut out = File::create(out_path)?;
-    let mut decoder = FrameDecoder::new(file);
-    let mut buffer = [0u8; BUFFER_SIZE];
-
-    // Read compressed data and write decompressed version
-    loop {
-        let bytes_read = decoder.read(&mut buffer)?;
-        if bytes_read == 0 { break };
-        out.write_all(&buffer[..bytes_read])?;
+/// Unpacks a tarball into a directory
+pub fn unpack_tar(tar: impl Read, dir_path: impl AsRef<Path>) -> Result<(), io::Error> {
+    let mut archive = tar::Archive::new(tar);
+    let dir_path = dir_path.as_ref();
+    fs::create_dir_all(dir_path)?;
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        entry.unpack_in(dir_path)?;
     };
 
-    out.flush()?;
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Decompresses and unpacks a compressed tarball (the format of older versions of `lazy-db`) in one pass
+pub fn unpack_compressed(path: impl AsRef<Path>, dir_path: impl AsRef<Path>) -> Result<(), io::Error> {
+    let file = File::open(path)?;
+    unpack_tar(FrameDecoder::new(file), dir_path)
+}
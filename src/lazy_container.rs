@@ -1,5 +1,7 @@
 use crate::*;
+use crate::lazy_link::{Links, LinkWriter};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use std::fs;
 
 /// Used for reading from a `LazyContainer` with less boiler-plate
@@ -12,6 +14,13 @@ macro_rules! write_container {
         Result::<(), LDBError>::Ok(())
     })()};
 
+    (($container:expr) $($item:ident)?$(($obj:expr))? = $value:expr) => {(|| {
+        let container = &$container;
+        $(container.write(stringify!($item), $value)?;)?
+        $(container.write($obj, $value)?;)?
+        Result::<(), LDBError>::Ok(())
+    })()};
+
     (($container:expr) /$($($con:ident)?$(($can:expr))?)/ *::$($item:ident)?$(($obj:expr))? = $func:ident($value:expr)) => {(|| {
         let mut container = &mut $container;
         $({
@@ -26,6 +35,22 @@ macro_rules! write_container {
         $(LazyData::$func(container.data_writer(stringify!($item))?, $value)?;)?
         $(LazyData::$func(container.data_writer($obj)?, $value)?;)?
         Result::<(), LDBError>::Ok(())
+    })()};
+
+    (($container:expr) /$($($con:ident)?$(($can:expr))?)/ *::$($item:ident)?$(($obj:expr))? = $value:expr) => {(|| {
+        let mut container = &mut $container;
+        $({
+            let con = $(stringify!($con))?$($can)?;
+            container = match container.read_container(con) {
+                Ok(x) => x,
+                Err(LDBError::DirNotFound(_)) => container.new_container(con)?,
+                Err(e) => return Err(e),
+            }
+        };)*
+
+        $(container.write(stringify!($item), $value)?;)?
+        $(container.write($obj, $value)?;)?
+        Result::<(), LDBError>::Ok(())
     })()}
 }
 
@@ -57,9 +82,26 @@ macro_rules! search_container {
     })()};
 }
 
+/// Serializes the in-place array mutations of this process, as each reads the array before modifying it
+static ARRAY_LOCK: Mutex<()> = Mutex::new(());
+
+/// Checks that a key names a single entry of a container (rather than a path) and isn't reserved for `lazy-db` (starting with `.`)
+pub(crate) fn check_key(key: &str) -> Result<(), LDBError> {
+    use std::path::Component;
+    let mut components = Path::new(key).components();
+    let single = matches!((components.next(), components.next()), (Some(Component::Normal(_)), None));
+    if !single || key.contains('/') || key.starts_with('.') { return Err(LDBError::InvalidKey(key.to_string())) };
+    Ok(())
+}
+
 /// A wrapper for a directory that holds individual `LazyData` files
+#[derive(Clone)]
 pub struct LazyContainer {
     path: PathBuf,
+    backend: Arc<dyn StorageBackend>,
+    read_only: bool,
+    /// How the database's links resolve and the index of them (`None` if it's not part of a `LazyDB`)
+    links: Option<Arc<Links>>,
 }
 
 impl LazyContainer {
@@ -73,81 +115,397 @@ impl LazyContainer {
         // Constructs self
         Ok(Self {
             path: path.to_path_buf(),
+            backend: Arc::new(FsBackend::new()),
+            read_only: false,
+            links: None,
         })
     }
 
     /// Loads a pre-existing `LazyContainer` directory at a specified path.
     /// 
     /// Will throw an error if the directory doesn't exist or there is an `io::Error`.
+    #[inline]
     pub fn load(path: impl AsRef<Path>) -> Result<Self, LDBError> {
+        Self::load_in(Arc::new(FsBackend::new()), path)
+    }
+
+    /// Initialises a new, empty `LazyContainer` at the specified path of a `StorageBackend`.
+    pub fn init_in(backend: Arc<dyn StorageBackend>, path: impl AsRef<Path>) -> Result<Self, LDBError> {
         let path = path.as_ref().to_path_buf();
 
         // Checks if path exists or not
-        if !path.is_dir() { return Err(LDBError::DirNotFound(path)) };
+        if backend.kind(&path) != Some(EntryKind::Container) {
+            unwrap_result!((backend.create_dir(&path)) err => LDBError::IOError(err));
+        }
 
         // Constructs self
         Ok(Self {
             path,
+            backend,
+            read_only: false,
+            links: None,
         })
     }
 
+    /// Loads a pre-existing `LazyContainer` at the specified path of a `StorageBackend`.
+    /// 
+    /// Will throw an error if the directory doesn't exist.
+    pub fn load_in(backend: Arc<dyn StorageBackend>, path: impl AsRef<Path>) -> Result<Self, LDBError> {
+        let path = path.as_ref().to_path_buf();
+
+        // Checks if path exists or not
+        if backend.kind(&path) != Some(EntryKind::Container) { return Err(LDBError::DirNotFound(path)) };
+
+        // Constructs self
+        Ok(Self {
+            path,
+            backend,
+            read_only: false,
+            links: None,
+        })
+    }
+
+    /// Makes the container (and every container gotten from it) refuse all writes
+    #[inline]
+    pub(crate) fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Makes the container (and every container gotten from it) resolve links like the database (see `LazyContainer::write_link`)
+    /// and keep the database's index of links up to date
+    #[inline]
+    pub(crate) fn with_links(mut self, links: Arc<Links>) -> Self {
+        self.links = Some(links);
+        self
+    }
+
+    /// Constructs a nested container that shares this container's backend, read-only mode and links
+    #[inline]
+    fn nested(&self, path: PathBuf) -> LazyContainer {
+        LazyContainer { path, backend: self.backend.clone(), read_only: self.read_only, links: self.links.clone() }
+    }
+
+    /// Returns `LDBError::ReadOnly` if the container is read-only
+    #[inline]
+    fn writable(&self, path: &Path) -> Result<(), LDBError> {
+        if self.read_only { return Err(LDBError::ReadOnly(path.to_path_buf())) };
+        Ok(())
+    }
+
+    /// Checks if the container refuses all writes (see `LazyDB::open_read_only`)
+    #[inline]
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Gets the `StorageBackend` that the container is stored in
+    #[inline]
+    pub fn backend(&self) -> &Arc<dyn StorageBackend> {
+        &self.backend
+    }
+
     /// Generates a `FileWrapper` in write mode from a key (like a relative file path)
     /// 
-    /// If the data already exists, it will try to remove it
+    /// The data is written to a temporary file which only replaces the existing data (if any) once the `FileWrapper` is finished,
+    /// so readers will always see either the old or the new value (all `LazyData::new_*` functions finish the `FileWrapper` for you)
+    /// 
+    /// **Note:** if the `FileWrapper` is dropped without calling `FileWrapper::finish`, nothing is written
     pub fn data_writer(&self, key: impl AsRef<Path>) -> Result<FileWrapper, LDBError> {
         let path = self.path.join(key);
-        if path.is_file() { let _ = fs::remove_file(&path); }; // if files exists try remove it
-        let file = unwrap_result!((fs::File::create(path)) err => LDBError::IOError(err));
-        Ok(FileWrapper::new_writer(file))
+        self.writable(&path)?;
+        let file = unwrap_result!((self.backend.open_writer(&path)) err => LDBError::IOError(err));
+        Ok(match &self.links {
+            Some(links) => FileWrapper::from_writer(LinkWriter::new(file, path, self.backend.clone(), links.clone())),
+            None => file,
+        })
+    }
+
+    /// Generates a `LazyWriter` that streams a `LazyType::Binary` value into `LazyData` at the specified key
+    /// 
+    /// The data only replaces the existing data (if any) once the writer is finished with `LazyWriter::finish`
+    #[inline]
+    pub fn binary_writer(&self, key: impl AsRef<Path>) -> Result<LazyWriter, LDBError> {
+        LazyWriter::new(self.data_writer(key)?, LazyType::Binary)
+    }
+
+    /// Generates a `LazyWriter` that streams a `LazyType::String` value into `LazyData` at the specified key
+    /// 
+    /// Writes of invalid utf-8 fail; the data only replaces the existing data (if any) once the writer is finished with `LazyWriter::finish`
+    #[inline]
+    pub fn string_writer(&self, key: impl AsRef<Path>) -> Result<LazyWriter, LDBError> {
+        LazyWriter::new(self.data_writer(key)?, LazyType::String)
+    }
+
+    /// Writes any value that implements `ToLazy` as `LazyData` at the specified key
+    /// 
+    /// If the data already exists, it will be replaced
+    #[inline]
+    pub fn write<T: ToLazy>(&self, key: impl AsRef<Path>, value: T) -> Result<(), LDBError> {
+        value.to_lazy(self.data_writer(key)?)
+    }
+
+    /// Sets the element at an index of a `LazyType::Array` in place, without rewriting the rest of the array
+    /// 
+    /// In place only if the database has a write-ahead log (see `LazyDB::enable_wal`), which redoes it if it's interrupted;
+    /// otherwise the array is atomically rewritten like `LazyContainer::write`
+    /// 
+    /// Returns `LDBError::IncorrectType` if the data isn't the correct array type
+    /// and `LDBError::IndexOutOfBounds` if the index isn't within the array
+    pub fn array_set<T: FixedArrayItem>(&self, key: impl AsRef<Path>, index: usize, value: T) -> Result<(), LDBError> {
+        let path = self.path.join(key);
+        self.writable(&path)?;
+        let _guard = ARRAY_LOCK.lock().unwrap();
+        let offset = LazyData::load_from(&*self.backend, &path)?.array_offset::<T>(index)?;
+
+        let mut bytes = Vec::with_capacity(T::SIZE);
+        value.write_bytes(&mut bytes);
+        unwrap_result!((self.backend.write_at(&path, offset, &bytes)) err => LDBError::IOError(err));
+        Ok(())
+    }
+
+    /// Appends elements onto the end of a `LazyType::Array` in place, creating the array if it doesn't exist
+    /// 
+    /// Only in place with a write-ahead log (see `LazyContainer::array_set`)
+    /// 
+    /// Returns `LDBError::IncorrectType` if the data isn't the correct array type
+    pub fn append_array<T: FixedArrayItem>(&self, key: impl AsRef<Path>, values: &[T]) -> Result<(), LDBError> {
+        let path = self.path.join(&key);
+        self.writable(&path)?;
+        let _guard = ARRAY_LOCK.lock().unwrap();
+        if self.backend.kind(&path) != Some(EntryKind::Data) {
+            return T::new_array(self.data_writer(key)?, values);
+        }
+
+        // Any partially appended element is overwritten
+        let len = LazyData::load_from(&*self.backend, &path)?.typed_array_len::<T>()?;
+        if values.is_empty() { return Ok(()) };
+        let mut bytes = Vec::with_capacity(values.len() * T::SIZE);
+        for value in values { value.write_bytes(&mut bytes) };
+        unwrap_result!((self.backend.write_at(&path, ARRAY_START + (len * T::SIZE) as u64, &bytes)) err => LDBError::IOError(err));
+        Ok(())
+    }
+
+    /// Shortens a `LazyType::Array` in place to a length (in elements), doing nothing if it's already shorter
+    /// 
+    /// Only in place with a write-ahead log (see `LazyContainer::array_set`)
+    /// 
+    /// Returns `LDBError::IncorrectType` if the data isn't the correct array type
+    pub fn truncate_array<T: FixedArrayItem>(&self, key: impl AsRef<Path>, len: usize) -> Result<(), LDBError> {
+        let path = self.path.join(key);
+        self.writable(&path)?;
+        let _guard = ARRAY_LOCK.lock().unwrap();
+        if len >= LazyData::load_from(&*self.backend, &path)?.typed_array_len::<T>()? { return Ok(()) };
+        unwrap_result!((self.backend.truncate(&path, ARRAY_START + (len * T::SIZE) as u64)) err => LDBError::IOError(err));
+        Ok(())
+    }
+
+    /// Removes the last element of a `LazyType::Array` in place and returns it (or `None` if it's empty)
+    /// 
+    /// Only in place with a write-ahead log (see `LazyContainer::array_set`); concurrent pops never return the same element
+    /// 
+    /// Returns `LDBError::IncorrectType` if the data isn't the correct array type
+    pub fn pop_array<T: FixedArrayItem>(&self, key: impl AsRef<Path>) -> Result<Option<T>, LDBError> {
+        let path = self.path.join(key);
+        self.writable(&path)?;
+        let _guard = ARRAY_LOCK.lock().unwrap();
+        let mut data = LazyData::load_from(&*self.backend, &path)?;
+        let len = data.typed_array_len::<T>()?;
+        if len == 0 { return Ok(None) };
+
+        let value = data.array_get::<T>(len - 1)?;
+        drop(data);
+        unwrap_result!((self.backend.truncate(&path, ARRAY_START + ((len - 1) * T::SIZE) as u64)) err => LDBError::IOError(err));
+        Ok(Some(value))
     }
 
     /// Generates a nested `LazyContainer` within this container
     /// 
     /// If container already exists it will **wipe** and **replace** it.
+    /// 
+    /// The old container is swapped out for the new one with renames before it is wiped, so it's never left partially wiped
     pub fn new_container(&self, key: impl AsRef<Path>) -> Result<LazyContainer, LDBError> {
         let path = self.path.join(&key);
-        if path.is_dir() { unwrap_result!((fs::remove_dir_all(&path)) err => LDBError::IOError(err)) }; // If exists wipe it
-        Ok(unwrap_result!((LazyContainer::init(path)) err => LDBError::IOError(err)))
+        self.writable(&path)?;
+        let result = if self.backend.kind(&path) == Some(EntryKind::Container) {
+            self.backend.replace_dir(&path)
+        } else {
+            self.backend.create_dir(&path)
+        };
+        unwrap_result!((result) err => LDBError::IOError(err));
+        Ok(self.nested(path))
     }
 
     /// Gets a nested `LazyContainer` within this container
     /// 
     /// If container already exists it will load it
-    /// Otherwise it will initialise a new one
+    /// Otherwise it will initialise a new one (or return `LDBError::DirNotFound` if the container is read-only)
     pub fn child_container(&self, key: impl AsRef<Path>) -> Result<LazyContainer, LDBError> {
         let path = self.path.join(&key);
-        if path.is_dir() { return self.read_container(key) }; // If exists load instead
-        Ok(unwrap_result!((LazyContainer::init(path)) err => LDBError::IOError(err)))
+        if self.backend.kind(&path) == Some(EntryKind::Container) || self.read_only { return self.read_container(key) }; // If exists (or can't be created) load instead
+        unwrap_result!((self.backend.create_dir(&path)) err => LDBError::IOError(err));
+        Ok(self.nested(path))
     }
 
     /// Reads nested `LazyData` within this container
+    #[inline]
     pub fn read_data(&self, key: impl AsRef<Path>) -> Result<LazyData, LDBError> {
-        let path = self.path.join(key);
-        if !path.is_file() { return Err(LDBError::FileNotFound(path)) };
-        LazyData::load(path)
+        LazyData::load_from(&*self.backend, self.path.join(key))
     }
 
     /// Reads nexted `LazyContainer` within this container
     pub fn read_container(&self, key: impl AsRef<Path>) -> Result<LazyContainer, LDBError> {
         let path = self.path.join(key);
-        if !path.is_dir() { return Err(LDBError::DirNotFound(path)) };
-        LazyContainer::load(path)
+        if self.backend.kind(&path) != Some(EntryKind::Container) { return Err(LDBError::DirNotFound(path)) };
+        Ok(self.nested(path))
+    }
+
+    /// Writes a link (see `LazyData::new_link`) at a key, returning `LDBError::InvalidValue` if it would point outside of the database
+    pub fn write_link(&self, key: impl AsRef<Path>, target: impl AsRef<Path>) -> Result<(), LDBError> {
+        let target = target.as_ref();
+        if let Some(links) = &self.links { links.target(&self.path.join(&key), target)?; };
+        LazyData::new_link(self.data_writer(key)?, target)
     }
 
     /// Tries to remove item at specified key; returns result
-    pub fn remove(&self, key: impl AsRef<Path>) -> Result<(), std::io::Error> {
+    /// 
+    /// Links that point to it are left dangling (see `LazyContainer::remove_with`)
+    #[inline]
+    pub fn remove(&self, key: impl AsRef<Path>) -> Result<(), LDBError> {
+        self.remove_with(key, RemovePolicy::Ignore)
+    }
+
+    /// Tries to remove item at specified key, refusing to (`RemovePolicy::Restrict`) or also removing (`RemovePolicy::Cascade`)
+    /// the links that point to it or anything within it
+    /// 
+    /// Links written through the database (however they were written) are found with it's index of links (see `LazyDB::reindex_links`)
+    pub fn remove_with(&self, key: impl AsRef<Path>, policy: RemovePolicy) -> Result<(), LDBError> {
         let path = self.path.join(key);
-        if path.is_dir() {
-            fs::remove_dir_all(path)
-        } else {
-            fs::remove_file(path)
+        self.writable(&path)?;
+        match &self.links {
+            Some(links) => links.remove(&*self.backend, &path, policy),
+            None => { unwrap_result!((self.backend.remove(&path)) err => LDBError::IOError(err)); Ok(()) },
         }
     }
 
     /// Tries to wipe container's contents; returns result
-    pub fn wipe(&self) -> Result<(), std::io::Error> {
-        fs::remove_dir_all(&self.path)?;
-        fs::create_dir_all(&self.path)
+    pub fn wipe(&self) -> Result<(), LDBError> {
+        self.writable(&self.path)?;
+        unwrap_result!((self.backend.clear_dir(&self.path)) err => LDBError::IOError(err));
+        Ok(())
+    }
+
+    /// Converts the nested `LazyContainer` at a key into an inline `LazyMap` stored at the same key
+    /// 
+    /// Containers nested within it become nested maps; the map is written next to the container and only then replaces it
+    pub fn pack_map(&self, key: impl AsRef<Path>) -> Result<(), LDBError> {
+        let path = self.path.join(key.as_ref());
+        self.writable(&path)?;
+        let map = LazyMap::from_container(&self.read_container(key)?)?;
+        let tmp = hidden_sibling(&path, "tmp");
+        self.write(tmp.file_name().unwrap_or_default(), &map)?;
+        self.replace_with(&tmp, &path)
+    }
+
+    /// Converts the inline `LazyMap` at a key into a nested `LazyContainer` at the same key, with each value as `LazyData`
+    /// 
+    /// Maps nested within it stay as `LazyData`, so unpacking isn't the exact reverse of `LazyContainer::pack_map` for nested containers;
+    /// the container is built next to the map and only then replaces it
+    pub fn unpack_map(&self, key: impl AsRef<Path>) -> Result<LazyContainer, LDBError> {
+        let path = self.path.join(key.as_ref());
+        self.writable(&path)?;
+        let map = self.read_data(key)?.collect_map()?;
+        map.check_keys()?;
+        let tmp = hidden_sibling(&path, "tmp");
+        unwrap_result!((self.backend.create_dir(&tmp)) err => LDBError::IOError(err));
+
+        // The links within it are indexed where they end up rather than where they're written
+        let unindexed = LazyContainer { path: tmp.clone(), backend: self.backend.clone(), read_only: false, links: None };
+        let written = map.write_to(&unindexed).and_then(|_| match &self.links {
+            Some(links) => links.record(&*self.backend, &map.links(&path)?, || self.replace_with(&tmp, &path)),
+            None => self.replace_with(&tmp, &path),
+        });
+        if let Err(e) = written {
+            if self.backend.kind(&tmp).is_some() { let _ = self.backend.remove(&tmp); };
+            return Err(e);
+        };
+        Ok(self.nested(path))
+    }
+
+    /// Moves a temporary entry over an entry (see `StorageBackend::rename`), removing the temporary entry if it fails
+    fn replace_with(&self, tmp: &Path, path: &Path) -> Result<(), LDBError> {
+        if let Err(err) = self.backend.rename(tmp, path) {
+            let _ = self.backend.remove(tmp);
+            return Err(LDBError::IOError(err));
+        };
+        Ok(())
+    }
+
+    /// Lists the names and kinds (`true` if it's a container) of everything within this container in alphabetical order
+    /// 
+    /// Entries starting with `.` are reserved for `lazy-db` itself and are skipped
+    pub(crate) fn entries(&self) -> Result<Vec<(String, bool)>, LDBError> {
+        let mut entries: Vec<(String, bool)> = unwrap_result!((self.backend.list(&self.path)) err => LDBError::IOError(err))
+            .into_iter()
+            .filter(|(key, _)| !key.starts_with('.'))
+            .map(|(key, kind)| (key, kind == EntryKind::Container))
+            .collect();
+        entries.sort();
+        Ok(entries)
+    }
+
+    /// Lists the keys of all the `LazyData` and `LazyContainer`s within this container in alphabetical order
+    /// 
+    /// Keys starting with `.` are reserved for `lazy-db` itself and are skipped
+    pub fn keys(&self) -> Result<impl Iterator<Item = String>, LDBError> {
+        Ok(self.entries()?.into_iter().map(|(key, _)| key))
+    }
+
+    /// Lists the keys and `LazyType`s of all the `LazyData` within this container in alphabetical order
+    /// 
+    /// Only reads the type byte of each `LazyData`
+    pub fn data_entries(&self) -> Result<impl Iterator<Item = (String, LazyType)>, LDBError> {
+        let mut result = Vec::new();
+        for (key, is_dir) in self.entries()? {
+            if is_dir { continue };
+            let lazy_type = self.read_data(&key)?.lazy_type;
+            result.push((key, lazy_type));
+        }
+        Ok(result.into_iter())
+    }
+
+    /// Lists the keys and `LazyContainer`s of all the nested containers within this container in alphabetical order
+    pub fn child_containers(&self) -> Result<impl Iterator<Item = (String, LazyContainer)>, LDBError> {
+        let this = self.clone();
+        Ok(self.entries()?
+            .into_iter()
+            .filter(|(_, is_dir)| *is_dir)
+            .map(move |(key, _)| {
+                let container = this.nested(this.path.join(&key));
+                (key, container)
+            }))
+    }
+
+    /// Counts the amount of `LazyData` and `LazyContainer`s within this container
+    pub fn len(&self) -> Result<usize, LDBError> {
+        Ok(self.entries()?.len())
+    }
+
+    /// Checks if this container holds no `LazyData` or `LazyContainer`s
+    pub fn is_empty(&self) -> Result<bool, LDBError> {
+        Ok(self.entries()?.is_empty())
+    }
+
+    /// Checks if `LazyData` exists at the specified key
+    #[inline]
+    pub fn contains_data(&self, key: impl AsRef<Path>) -> bool {
+        self.backend.kind(&self.path.join(key)) == Some(EntryKind::Data)
+    }
+
+    /// Checks if a nested `LazyContainer` exists at the specified key
+    #[inline]
+    pub fn contains_container(&self, key: impl AsRef<Path>) -> bool {
+        self.backend.kind(&self.path.join(key)) == Some(EntryKind::Container)
     }
 
     /// Returns a reference to the container's path
@@ -1,10 +1,24 @@
 mod reading;
 mod writing;
 mod file_wrapper;
+mod streaming;
+mod array;
+mod values;
+mod map;
+mod tensor;
+#[cfg(feature = "mmap")]
+mod mapping;
 
 pub use reading::*;
 pub use writing::*;
 pub use file_wrapper::*;
+pub use streaming::*;
+pub use array::*;
+pub use values::*;
+pub use map::*;
+pub use tensor::*;
+#[cfg(feature = "mmap")]
+pub use mapping::*;
 
 use std::path::{Path, PathBuf};
 use crate::*;
@@ -13,18 +27,32 @@ pub struct LazyData {
     pub path: PathBuf,
     pub lazy_type: LazyType,
     wrapper: FileWrapper,
+    /// Where the data (it's type byte) starts within the file, as it may be nested within other data
+    start: u64,
+}
+
+/// Formats the `LazyData` as it's path and type (not it's contents)
+impl std::fmt::Debug for LazyData {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("LazyData").field("path", &self.path).field("lazy_type", &self.lazy_type).finish_non_exhaustive()
+    }
 }
 
 impl LazyData {
     pub fn load(path: impl AsRef<Path>) -> Result<Self, LDBError> {
+        Self::load_from(&FsBackend::new(), path)
+    }
+
+    /// Loads `LazyData` at a path of a `StorageBackend`
+    pub fn load_from(backend: &dyn StorageBackend, path: impl AsRef<Path>) -> Result<Self, LDBError> {
         let path = path.as_ref();
 
         // Check for the existance of the path and if it's a file
-        if !path.is_file() { return Err(LDBError::FileNotFound(path.to_path_buf())) };
+        if backend.kind(path) != Some(EntryKind::Data) { return Err(LDBError::FileNotFound(path.to_path_buf())) };
 
         // Get the reader
         let mut reader =
-            FileWrapper::new_reader(unwrap_result!((std::fs::File::open(path)) err => LDBError::IOError(err)));
+            unwrap_result!((backend.open_reader(path)) err => LDBError::IOError(err));
 
         // Reads the byte repr of it's `LazyType`
         let lazy_type =
@@ -34,10 +62,90 @@ impl LazyData {
             path: path.to_path_buf(),
             lazy_type,
             wrapper: reader,
+            start: 0,
         })
     }
 
     pub fn get_path(&self) -> PathBuf {
         self.path.clone()
     }
-}
\ No newline at end of file
+
+    /// Reads the type byte of data nested within this data (like an option's value), which starts where the reader is
+    pub(crate) fn nested(mut self) -> Result<Self, LDBError> {
+        let start = unwrap_result!((self.wrapper.seek(std::io::SeekFrom::Current(0))) err => LDBError::IOError(err));
+        let lazy_type =
+            LazyType::try_from(self.wrapper.read(1)?[0])?;
+
+        Ok(Self {
+            path: self.path,
+            lazy_type,
+            wrapper: self.wrapper,
+            start,
+        })
+    }
+
+    /// Seeks to an offset within the data (from it's type byte)
+    pub(crate) fn seek_data(&mut self, offset: u64) -> Result<(), LDBError> {
+        unwrap_result!((self.wrapper.seek(std::io::SeekFrom::Start(self.start + offset))) err => LDBError::IOError(err));
+        Ok(())
+    }
+
+    /// Gets the length of the data (including it's type byte)
+    pub(crate) fn data_len(&mut self) -> Result<u64, LDBError> {
+        let end = unwrap_result!((self.wrapper.seek(std::io::SeekFrom::End(0))) err => LDBError::IOError(err));
+        Ok(end - self.start)
+    }
+}
+/// A type that can be stored as an element of a `LazyType::Array`
+pub trait LazyArrayItem: Sized {
+    /// Creates a new `LazyData` file with an array of `Self`
+    fn new_array(file: FileWrapper, value: &[Self]) -> Result<(), LDBError>;
+    /// Collects a `LazyData` array of `Self`
+    fn collect_array(data: LazyData) -> Result<Box<[Self]>, LDBError>;
+}
+
+macro_rules! array_item {
+    ($(($new:ident, $collect:ident) $type:ty;)*) => {$(
+        impl LazyArrayItem for $type {
+            #[inline]
+            fn new_array(file: FileWrapper, value: &[Self]) -> Result<(), LDBError> {
+                LazyData::$new(file, value)
+            }
+
+            #[inline]
+            fn collect_array(data: LazyData) -> Result<Box<[Self]>, LDBError> {
+                data.$collect()
+            }
+        }
+    )*};
+}
+
+array_item! {
+    (new_u8_array, collect_u8_array) u8;
+    (new_u16_array, collect_u16_array) u16;
+    (new_u32_array, collect_u32_array) u32;
+    (new_u64_array, collect_u64_array) u64;
+    (new_u128_array, collect_u128_array) u128;
+    (new_i8_array, collect_i8_array) i8;
+    (new_i16_array, collect_i16_array) i16;
+    (new_i32_array, collect_i32_array) i32;
+    (new_i64_array, collect_i64_array) i64;
+    (new_i128_array, collect_i128_array) i128;
+    (new_f32_array, collect_f32_array) f32;
+    (new_f64_array, collect_f64_array) f64;
+    (new_bool_array, collect_bool_array) bool;
+    (new_string_array, collect_string_array) String;
+    (new_binary_array, collect_binary_array) Box<[u8]>;
+}
+
+impl LazyArrayItem for Vec<u8> {
+    #[inline]
+    fn new_array(file: FileWrapper, value: &[Self]) -> Result<(), LDBError> {
+        LazyData::new_binary_array(file, value)
+    }
+
+    #[inline]
+    fn collect_array(data: LazyData) -> Result<Box<[Self]>, LDBError> {
+        Ok(data.collect_binary_array()?.into_vec().into_iter().map(Vec::from).collect())
+    }
+}
@@ -0,0 +1,371 @@
+use super::*;
+use std::io::SeekFrom;
+use std::marker::PhantomData;
+use std::ops::Range;
+
+/// A fixed-width number type that is stored (big-endian) as an element of a `LazyType::Array`
+pub trait FixedArrayItem: LazyArrayItem + Copy {
+    /// The `LazyType` of the array's elements
+    const LAZY_TYPE: LazyType;
+    /// The size of an element in bytes
+    const SIZE: usize;
+    /// Converts the (big-endian) bytes of an element
+    fn from_bytes(bytes: &[u8]) -> Self;
+    /// Appends the (big-endian) bytes of an element
+    fn write_bytes(self, out: &mut Vec<u8>);
+}
+
+macro_rules! fixed_array_item {
+    ($($type:ty = $lazy_type:ident;)*) => {
+        $(impl FixedArrayItem for $type {
+            const LAZY_TYPE: LazyType = LazyType::$lazy_type;
+            const SIZE: usize = std::mem::size_of::<$type>();
+
+            #[inline]
+            fn from_bytes(bytes: &[u8]) -> Self {
+                <$type>::from_be_bytes(bytes.try_into().unwrap())
+            }
+
+            #[inline]
+            fn write_bytes(self, out: &mut Vec<u8>) {
+                out.extend_from_slice(&self.to_be_bytes());
+            }
+        })*
+
+        /// The size in bytes of the elements of an array of a `LazyType`
+        fn element_size(lazy_type: LazyType) -> Result<usize, LDBError> {
+            match lazy_type {
+                $(LazyType::$lazy_type => Ok(std::mem::size_of::<$type>()),)*
+                _ => Err(LDBError::IncorrectType(lazy_type, String::from("a fixed-width number type"))),
+            }
+        }
+    };
+}
+
+fixed_array_item! {
+    u8 = U8;
+    u16 = U16;
+    u32 = U32;
+    u64 = U64;
+    u128 = U128;
+    i8 = I8;
+    i16 = I16;
+    i32 = I32;
+    i64 = I64;
+    i128 = I128;
+    f32 = F32;
+    f64 = F64;
+}
+
+/// The offset of the first element of a `LazyType::Array` (after the type and element type bytes)
+pub(crate) const ARRAY_START: u64 = 2;
+
+/// An iterator over the elements of a `LazyType::Array` that reads them from the file as they're needed
+///
+/// Skipping elements (with `Iterator::nth` or `Iterator::skip`) seeks past them instead of reading them
+pub struct LazyArrayIter<T: FixedArrayItem> {
+    wrapper: FileWrapper,
+    /// Where the array's elements start within the file
+    start: u64,
+    index: usize,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T: FixedArrayItem> Iterator for LazyArrayIter<T> {
+    type Item = Result<T, LDBError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.len { return None };
+        self.index += 1;
+        let result = self.wrapper.read(T::SIZE).map(|x| T::from_bytes(&x));
+        if result.is_err() { self.index = self.len }; // stop after an error
+        Some(result)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.len - self.index;
+        (remaining, Some(remaining))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        if n >= self.len - self.index {
+            self.index = self.len;
+            return None;
+        }
+
+        self.index += n;
+        let offset = self.start + (self.index * T::SIZE) as u64;
+        if let Err(e) = self.wrapper.seek(SeekFrom::Start(offset)) {
+            self.index = self.len;
+            return Some(Err(LDBError::IOError(e)));
+        }
+        self.next()
+    }
+}
+
+impl<T: FixedArrayItem> ExactSizeIterator for LazyArrayIter<T> {}
+
+/// A variable-width type that is stored (prefixed with it's length as a big-endian `u64`) as an element of a `LazyType::Array`
+pub trait VarArrayItem: LazyArrayItem {
+    /// The `LazyType` of the array's elements
+    const LAZY_TYPE: LazyType;
+    /// Converts the bytes of an element
+    fn from_bytes(bytes: Box<[u8]>) -> Result<Self, LDBError>;
+}
+
+impl VarArrayItem for String {
+    const LAZY_TYPE: LazyType = LazyType::String;
+
+    #[inline]
+    fn from_bytes(bytes: Box<[u8]>) -> Result<Self, LDBError> {
+        String::from_utf8(bytes.into_vec()).map_err(|e| LDBError::InvalidUTF8String(e.into_bytes().into_boxed_slice()))
+    }
+}
+
+impl VarArrayItem for Box<[u8]> {
+    const LAZY_TYPE: LazyType = LazyType::Binary;
+
+    #[inline]
+    fn from_bytes(bytes: Box<[u8]>) -> Result<Self, LDBError> {
+        Ok(bytes)
+    }
+}
+
+impl VarArrayItem for Vec<u8> {
+    const LAZY_TYPE: LazyType = LazyType::Binary;
+
+    #[inline]
+    fn from_bytes(bytes: Box<[u8]>) -> Result<Self, LDBError> {
+        Ok(bytes.into_vec())
+    }
+}
+
+/// An iterator over the elements of a variable-width `LazyType::Array` that reads them from the file as they're needed
+///
+/// Skipping elements (with `Iterator::nth` or `Iterator::skip`) seeks past them instead of reading them
+pub struct LazyVarArrayIter<T: VarArrayItem> {
+    wrapper: FileWrapper,
+    remaining: u64,
+    done: bool,
+    _marker: PhantomData<T>,
+}
+
+/// Reads the length of the next element of a variable-width array (or `None` at the end of the array)
+/// 
+/// `remaining` is the amount of bytes left in the array, which the element is taken out of
+fn next_len(wrapper: &mut FileWrapper, remaining: &mut u64) -> Result<Option<usize>, LDBError> {
+    let at_end = unwrap_result!((wrapper.reader().fill_buf()) err => LDBError::IOError(err)).is_empty();
+    if at_end { return Ok(None) };
+    let len = u64::from_be_bytes(wrapper.read(8)?.as_ref().try_into().unwrap());
+    *remaining = remaining.saturating_sub(8);
+    // The element can't be longer than what's left of the array (so a corrupt length isn't allocated)
+    if len > *remaining { return Err(LDBError::InvalidValue(LazyType::Array, format!("element of '{len}' bytes is longer than the data"))) };
+    *remaining -= len;
+    Ok(Some(len as usize))
+}
+
+/// Seeks past the next `n` elements of a variable-width array, returning how many there were
+fn skip_elements(wrapper: &mut FileWrapper, remaining: &mut u64, n: usize) -> Result<usize, LDBError> {
+    for i in 0..n {
+        match next_len(wrapper, remaining)? {
+            Some(len) => unwrap_result!((wrapper.skip(len as i64)) err => LDBError::IOError(err)),
+            None => return Ok(i),
+        }
+    }
+    Ok(n)
+}
+
+impl<T: VarArrayItem> Iterator for LazyVarArrayIter<T> {
+    type Item = Result<T, LDBError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done { return None };
+        let result = match next_len(&mut self.wrapper, &mut self.remaining) {
+            Ok(Some(len)) => self.wrapper.read(len).and_then(T::from_bytes),
+            Ok(None) => {
+                self.done = true;
+                return None;
+            },
+            Err(e) => Err(e),
+        };
+        if result.is_err() { self.done = true }; // stop after an error
+        Some(result)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        if self.done { return None };
+        match skip_elements(&mut self.wrapper, &mut self.remaining, n) {
+            Ok(x) if x == n => self.next(),
+            Ok(_) => {
+                self.done = true;
+                None
+            },
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            },
+        }
+    }
+}
+
+impl LazyData {
+    /// Reads the element type and length of a `LazyType::Array` without reading it's elements
+    fn array_header(&mut self) -> Result<(LazyType, usize), LDBError> {
+        if self.lazy_type != LazyType::Array { return Err(LDBError::IncorrectType(self.lazy_type, String::from("LazyType::Array"))) };
+
+        self.seek_data(1)?;
+        let array_type =
+            LazyType::try_from(self.wrapper.read(1)?[0])?;
+
+        let len = match array_type {
+            LazyType::True => self.bool_array_len()?,
+            LazyType::String | LazyType::Binary => {
+                let mut remaining = self.data_len()?.saturating_sub(ARRAY_START);
+                self.seek_data(ARRAY_START)?;
+                skip_elements(&mut self.wrapper, &mut remaining, usize::MAX)? // seeks past every element
+            },
+            _ => {
+                let size = element_size(array_type)?;
+                let end = self.data_len()?;
+                (end.saturating_sub(ARRAY_START) / size as u64) as usize
+            },
+        };
+
+        Ok((array_type, len))
+    }
+
+    /// Checks that a `LazyType::Array` is an array of booleans and gets it's length (leaving the reader at the first element)
+    pub(crate) fn bool_array_len(&mut self) -> Result<usize, LDBError> {
+        if self.lazy_type != LazyType::Array { return Err(LDBError::IncorrectType(self.lazy_type, String::from("LazyType::Array"))) };
+        self.seek_data(1)?;
+        let header = self.wrapper.read(2)?;
+        let array_type = LazyType::try_from(header[0])?;
+        if array_type != LazyType::True { return Err(LDBError::IncorrectType(array_type, String::from("LazyType::True"))) };
+
+        let end = self.data_len()?;
+        self.seek_data(ARRAY_START + 1)?;
+        Ok(((end.saturating_sub(ARRAY_START + 1) * 8) as usize).saturating_sub(header[1] as usize))
+    }
+
+    /// Checks the element type of a `LazyType::Array` and gets it's length
+    pub(crate) fn typed_array_len<T: FixedArrayItem>(&mut self) -> Result<usize, LDBError> {
+        let (array_type, len) = self.array_header()?;
+        if array_type != T::LAZY_TYPE { return Err(LDBError::IncorrectType(array_type, format!("LazyType::{:?}", T::LAZY_TYPE))) };
+        Ok(len)
+    }
+
+    /// Gets the offset within the file of the element at an index of a `LazyType::Array`
+    pub(crate) fn array_offset<T: FixedArrayItem>(&mut self, index: usize) -> Result<u64, LDBError> {
+        let len = self.typed_array_len::<T>()?;
+        if index >= len { return Err(LDBError::IndexOutOfBounds(index, len)) };
+        Ok(ARRAY_START + (index * T::SIZE) as u64)
+    }
+
+    /// Gets the amount of elements in a `LazyType::Array` without reading them
+    ///
+    /// For arrays of strings or binaries this seeks past every element
+    #[inline]
+    pub fn array_len(&mut self) -> Result<usize, LDBError> {
+        Ok(self.array_header()?.1)
+    }
+
+    /// Reads only the element at an index of a `LazyType::Array`
+    ///
+    /// Returns `LDBError::IncorrectType` if the LazyData type is not the correct array type
+    /// and `LDBError::IndexOutOfBounds` if the index isn't within the array
+    pub fn array_get<T: FixedArrayItem>(&mut self, index: usize) -> Result<T, LDBError> {
+        let offset = self.array_offset::<T>(index)?;
+        self.seek_data(offset)?;
+        Ok(T::from_bytes(&self.wrapper.read(T::SIZE)?))
+    }
+
+    /// Reads only the elements within a range of a `LazyType::Array`
+    ///
+    /// Returns `LDBError::IncorrectType` if the LazyData type is not the correct array type
+    /// and `LDBError::IndexOutOfBounds` if the range isn't within the array
+    pub fn array_range<T: FixedArrayItem>(&mut self, range: Range<usize>) -> Result<Box<[T]>, LDBError> {
+        let len = self.typed_array_len::<T>()?;
+        if range.end > len { return Err(LDBError::IndexOutOfBounds(range.end, len)) };
+        if range.is_empty() { return Ok(Box::new([])) };
+
+        self.seek_data(ARRAY_START + (range.start * T::SIZE) as u64)?;
+        let bytes = self.wrapper.read(range.len() * T::SIZE)?;
+        Ok(bytes.chunks_exact(T::SIZE).map(T::from_bytes).collect())
+    }
+
+    /// Iterates over the elements of a `LazyType::Array`, reading them as they're needed
+    ///
+    /// Returns `LDBError::IncorrectType` if the LazyData type is not the correct array type
+    pub fn array_iter<T: FixedArrayItem>(mut self) -> Result<LazyArrayIter<T>, LDBError> {
+        let len = self.typed_array_len::<T>()?;
+        self.seek_data(ARRAY_START)?;
+        Ok(LazyArrayIter {
+            start: self.start + ARRAY_START,
+            wrapper: self.wrapper,
+            index: 0,
+            len,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Reads only the boolean at an index of a `LazyType::Array` of booleans
+    ///
+    /// Returns `LDBError::IncorrectType` if the LazyData type is not an array of booleans
+    /// and `LDBError::IndexOutOfBounds` if the index isn't within the array
+    pub fn array_get_bool(&mut self, index: usize) -> Result<bool, LDBError> {
+        let len = self.bool_array_len()?;
+        if index >= len { return Err(LDBError::IndexOutOfBounds(index, len)) };
+        self.seek_data(ARRAY_START + 1 + (index / 8) as u64)?;
+        Ok(self.wrapper.read(1)?[0] & (0x80 >> (index % 8)) != 0)
+    }
+
+    /// Reads only the string at an index of a `LazyType::Array` of strings (seeking past the elements before it)
+    ///
+    /// Returns `LDBError::IncorrectType` if the LazyData type is not an array of strings
+    /// and `LDBError::IndexOutOfBounds` if the index isn't within the array
+    #[inline]
+    pub fn array_get_string(self, index: usize) -> Result<String, LDBError> {
+        self.var_array_get(index)
+    }
+
+    /// Reads only the binary at an index of a `LazyType::Array` of binaries (seeking past the elements before it)
+    ///
+    /// Returns `LDBError::IncorrectType` if the LazyData type is not an array of binaries
+    /// and `LDBError::IndexOutOfBounds` if the index isn't within the array
+    #[inline]
+    pub fn array_get_binary(self, index: usize) -> Result<Box<[u8]>, LDBError> {
+        self.var_array_get(index)
+    }
+
+    /// Reads only the element at an index of a variable-width `LazyType::Array`
+    fn var_array_get<T: VarArrayItem>(self, index: usize) -> Result<T, LDBError> {
+        let mut iter = self.var_array_iter::<T>()?;
+        let skipped = skip_elements(&mut iter.wrapper, &mut iter.remaining, index)?;
+        match iter.next() {
+            Some(x) if skipped == index => x,
+            _ => Err(LDBError::IndexOutOfBounds(index, skipped)),
+        }
+    }
+
+    /// Iterates over the elements of a variable-width `LazyType::Array` (of strings or binaries), reading them as they're needed
+    ///
+    /// Returns `LDBError::IncorrectType` if the LazyData type is not the correct array type
+    pub fn var_array_iter<T: VarArrayItem>(mut self) -> Result<LazyVarArrayIter<T>, LDBError> {
+        if self.lazy_type != LazyType::Array { return Err(LDBError::IncorrectType(self.lazy_type, String::from("LazyType::Array"))) };
+        self.seek_data(1)?;
+        let array_type =
+            LazyType::try_from(self.wrapper.read(1)?[0])?;
+        if array_type != T::LAZY_TYPE { return Err(LDBError::IncorrectType(array_type, format!("LazyType::{:?}", T::LAZY_TYPE))) };
+        let remaining = self.data_len()?.saturating_sub(ARRAY_START);
+        self.seek_data(ARRAY_START)?;
+
+        Ok(LazyVarArrayIter {
+            wrapper: self.wrapper,
+            remaining,
+            done: false,
+            _marker: PhantomData,
+        })
+    }
+}
@@ -1,10 +1,110 @@
 use super::*;
-use std::io::{Read, Write, BufReader, BufWriter, Error};
-use std::fs::File;
+use std::io::{Read, BufRead, Write, Seek, SeekFrom, BufReader, BufWriter, Error};
+use std::fs::{self, File};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use crate::lazy_wal::Wal;
 
+/// A reader or writer of the file of `LazyData`
+/// 
+/// **Note:** atomic writers (like those from `LazyContainer::data_writer`) discard everything written to them
+/// unless they're finished with `FileWrapper::finish` (all `LazyData::new_*` functions finish them for you)
+#[must_use = "writers discard their data unless they're finished with `FileWrapper::finish`"]
 pub enum FileWrapper {
     Reader(BufReader<File>),
     Writer(BufWriter<File>),
+    Atomic(AtomicWriter),
+    /// A reader from any `StorageBackend`
+    Stream(BufReader<Box<dyn StorageReader>>),
+    /// A writer to any `StorageBackend` (committed once finished)
+    Sink(Box<dyn StorageWriter>),
+}
+
+/// A seekable reader returned by `StorageBackend::open_reader` (through `FileWrapper::from_reader`)
+pub trait StorageReader: Read + Seek + Send {}
+impl<T: Read + Seek + Send> StorageReader for T {}
+
+/// A writer to a temporary sibling file that is only renamed into place once it has been fully written
+/// 
+/// If it's dropped before being finished, the temporary file is removed and the original file is left untouched
+pub struct AtomicWriter {
+    writer: Option<BufWriter<File>>,
+    tmp_path: PathBuf,
+    path: PathBuf,
+    wal: Option<(Arc<Wal>, u64)>,
+}
+
+impl AtomicWriter {
+    /// Flushes and syncs the temporary file and then renames it into place
+    fn commit(mut self) -> Result<(), Error> {
+        let writer = self.writer.take().unwrap();
+        let file = writer.into_inner().map_err(|e| e.into_error())?;
+        file.sync_all()?;
+        if let Some((wal, id)) = &self.wal { wal.commit(*id)? };
+        fs::rename(&self.tmp_path, &self.path)?;
+        sync_parent(&self.path)?;
+        if let Some((wal, id)) = &self.wal { wal.end(*id)? };
+        Ok(())
+    }
+}
+
+impl Drop for AtomicWriter {
+    fn drop(&mut self) {
+        if self.writer.take().is_some() { // if not committed, discard it
+            let _ = fs::remove_file(&self.tmp_path);
+            if let Some((wal, id)) = &self.wal { let _ = wal.end(*id); };
+        }
+    }
+}
+
+/// Generates a unique, hidden sibling path of a file or directory for temporary use
+pub(crate) fn hidden_sibling(path: &Path, tag: &str) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let name = path.file_name().map(|x| x.to_string_lossy().to_string()).unwrap_or_default();
+    let id = COUNTER.fetch_add(1, Ordering::Relaxed);
+    path.with_file_name(format!(".{name}.{tag}-{}-{id}", std::process::id()))
+}
+
+/// Parses the name of a temporary file or directory (`.{name}.{tag}-{pid}-{id}`, see `hidden_sibling`)
+/// into the name it's for and whether it's an old container that was swapped out
+pub(crate) fn parse_leftover(name: &str) -> Option<(String, bool)> {
+    name.strip_prefix('.').and_then(|x| x.rsplit_once('.')).and_then(|(target, suffix)| {
+        let mut parts = suffix.split('-');
+        let tag = parts.next()?;
+        let numeric = parts.clone().count() == 2 && parts.all(|x| !x.is_empty() && x.bytes().all(|b| b.is_ascii_digit()));
+        (numeric && (tag == "tmp" || tag == "old")).then(|| (target.to_string(), tag == "old"))
+    })
+}
+
+/// Removes the temporary files and directories (see `hidden_sibling`) left within a directory by a crash,
+/// putting back the old entry if `StorageBackend::replace_dir` or `StorageBackend::rename` was interrupted between it's renames
+/// 
+/// Only safe while nothing else is writing to the directory
+pub(crate) fn remove_leftovers(dir: &Path) -> Result<(), Error> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+
+        match parse_leftover(&name) {
+            Some((target, is_old)) => {
+                let target = dir.join(target);
+                if is_old && fs::symlink_metadata(&target).is_err() { fs::rename(&path, &target)?; continue }; // the old entry was moved out but the new one never moved in
+                if path.is_dir() { fs::remove_dir_all(&path)? } else { fs::remove_file(&path)? };
+            },
+            None if entry.file_type()?.is_dir() => remove_leftovers(&path)?,
+            None => (),
+        }
+    }
+    Ok(())
+}
+
+/// Syncs the directory that a path is in so that renames and removals within it are durable
+pub(crate) fn sync_parent(path: &Path) -> Result<(), Error> {
+    match path.parent() {
+        Some(x) if !x.as_os_str().is_empty() => File::open(x)?.sync_all(),
+        _ => Ok(()),
+    }
 }
 
 impl FileWrapper {
@@ -22,47 +122,121 @@ impl FileWrapper {
         )
     }
 
+    /// Constructs a new `FileWrapper::Atomic` varient that replaces the file at the path once finished
+    pub fn new_atomic(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::new_atomic_logged(path, None)
+    }
+
+    /// Constructs a new `FileWrapper::Atomic` varient that logs it's progress to a write-ahead log
+    pub(crate) fn new_atomic_logged(path: impl AsRef<Path>, wal: Option<&Arc<Wal>>) -> Result<Self, Error> {
+        let path = path.as_ref().to_path_buf();
+        let tmp_path = hidden_sibling(&path, "tmp");
+        let wal = match wal {
+            Some(wal) => Some((wal.clone(), wal.begin_write(&tmp_path, &path)?)),
+            None => None,
+        };
+        let file = File::create(&tmp_path)?;
+        Ok(Self::Atomic(AtomicWriter {
+            writer: Some(BufWriter::new(file)),
+            tmp_path,
+            path,
+            wal,
+        }))
+    }
+
+    /// Constructs a new `FileWrapper::Stream` varient (for implementing `StorageBackend::open_reader`)
+    pub fn from_reader(reader: impl StorageReader + 'static) -> Self {
+        Self::Stream(
+            BufReader::new(Box::new(reader)),
+        )
+    }
+
+    /// Constructs a new `FileWrapper::Sink` varient (for implementing `StorageBackend::open_writer`)
+    pub fn from_writer(writer: impl StorageWriter + 'static) -> Self {
+        Self::Sink(
+            Box::new(writer),
+        )
+    }
+
+    pub(crate) fn writer(&mut self) -> &mut dyn Write {
+        match self {
+            Self::Writer(w) => w,
+            Self::Atomic(a) => a.writer.as_mut().unwrap(),
+            Self::Sink(w) => w,
+            _ => panic!("You cannot write on a reader"), // Change later to use better error handling
+        }
+    }
+
+    pub(crate) fn reader(&mut self) -> &mut dyn BufRead {
+        match self {
+            Self::Reader(r) => r,
+            Self::Stream(r) => r,
+            _ => panic!("You cannot read on a writer"), // Change later to use better error handling
+        }
+    }
+
+    /// Seeks within a reader
+    pub(crate) fn seek(&mut self, pos: SeekFrom) -> Result<u64, Error> {
+        match self {
+            Self::Reader(r) => r.seek(pos),
+            Self::Stream(r) => r.seek(pos),
+            _ => panic!("You cannot seek on a writer"), // Change later to use better error handling
+        }
+    }
+
+    /// Skips bytes of a reader (keeping what's already buffered if it can)
+    pub(crate) fn skip(&mut self, offset: i64) -> Result<(), Error> {
+        match self {
+            Self::Reader(r) => r.seek_relative(offset),
+            Self::Stream(r) => r.seek_relative(offset),
+            _ => panic!("You cannot seek on a writer"), // Change later to use better error handling
+        }
+    }
+
     /// Writes a byte slice into the file
     pub fn write(&mut self, byte: &[u8]) -> Result<(), LDBError> {
-        let mut writer = if let Self::Writer(w) = self { w }
-            else { panic!("You cannot write on a reader") }; // Change later to use better error handling
-        unwrap_result!((Write::write_all(&mut writer, byte)) err => LDBError::IOError(err));
+        unwrap_result!((self.writer().write_all(byte)) err => LDBError::IOError(err));
         Ok(())
     }
 
     /// Reads a set amount of bytes from a file by padding out undefined portions with 0u8
     pub fn read(&mut self, length: usize) -> Result<Box<[u8]>, LDBError> {
-        let mut reader = if let Self::Reader(r) = self { r }
-            else { panic!("You cannot read on a writer") }; // Change later to use better error handling
         let mut buffer = vec![0u8; length].into_boxed_slice();
-        unwrap_result!((Read::read_exact(&mut reader, &mut buffer)) err => LDBError::IOError(err));
+        unwrap_result!((self.reader().read_exact(&mut buffer)) err => LDBError::IOError(err));
         Ok(buffer)
     }
 
+    /// Reads a set amount of bytes from a file, or `None` if the file ends before then
     pub fn read_opt(&mut self, length: usize) -> Result<Option<Box<[u8]>>, LDBError> {
-        let reader = if let Self::Reader(r) = self { r }
-            else { panic!("You cannot read on a writer") }; // Change later to use better error handling
+        let reader = self.reader();
         let mut buffer = vec![0u8; length].into_boxed_slice();
-        let read = unwrap_result!((reader.read(&mut buffer)) err => LDBError::IOError(err));
-        if read < length { Ok(None) }
-        else { Ok(Some(buffer)) }
+        let mut read = 0;
+        while read < length { // a single read may stop short at the end of the buffer
+            match unwrap_result!((reader.read(&mut buffer[read..])) err => LDBError::IOError(err)) {
+                0 => return Ok(None),
+                x => read += x,
+            }
+        }
+        Ok(Some(buffer))
     }
 
     /// Deconstruct the wrapper properly with all of the buffers and such
+    /// 
+    /// For `FileWrapper::Atomic` this is what renames the written file into place
     pub fn finish(self) -> Result<(), Error> {
         match self {
-            Self::Reader(_) => (),
+            Self::Reader(_) | Self::Stream(_) => (),
             Self::Writer(mut w) => w.flush()?,
+            Self::Atomic(a) => a.commit()?,
+            Self::Sink(w) => w.commit()?,
         };
         Ok(())
     }
 
     /// Reads to the end of the file (consumes wrapper)
-    pub fn read_to_end(self) -> Result<Box<[u8]>, LDBError> {
-        let mut reader = if let Self::Reader(r) = self { r }
-            else { panic!("You cannot read on a writer") }; // Change later to use better error handling
+    pub fn read_to_end(mut self) -> Result<Box<[u8]>, LDBError> {
         let mut buffer = Vec::new();
-        unwrap_result!((reader.read_to_end(&mut buffer)) err => LDBError::IOError(err));
+        unwrap_result!((self.reader().read_to_end(&mut buffer)) err => LDBError::IOError(err));
         Ok(buffer.into_boxed_slice())
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,245 @@
+use super::*;
+use std::io::{self, Write, Cursor};
+use std::sync::{Arc, Mutex};
+
+/// A small set of typed key/value pairs stored inline within a single `LazyData` file (`LazyType::Map`)
+///
+/// Each value is stored as `LazyData` (with it's own `LazyType`), so it's read the same way as data within a `LazyContainer`,
+/// but without the cost of a file for every value
+///
+/// ## Layout
+/// After the type byte, each entry is it's key (length-prefixed `u32`) and then it's value (length-prefixed `u64`)
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LazyMap {
+    entries: Vec<(String, Box<[u8]>)>,
+}
+
+/// A `StorageWriter` that hands it's bytes over once it's committed (for encoding `LazyData` in memory)
+struct Encoder {
+    buffer: Vec<u8>,
+    out: Arc<Mutex<Vec<u8>>>,
+}
+
+impl Write for Encoder {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> Result<usize, io::Error> {
+        self.buffer.write(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<(), io::Error> {
+        Ok(())
+    }
+}
+
+impl StorageWriter for Encoder {
+    fn commit(self: Box<Self>) -> Result<(), io::Error> {
+        *self.out.lock().unwrap() = self.buffer;
+        Ok(())
+    }
+}
+
+/// Encodes a value as the bytes of a `LazyData` file in memory
+fn encode(value: impl ToLazy) -> Result<Box<[u8]>, LDBError> {
+    let out = Arc::new(Mutex::new(Vec::new()));
+    value.to_lazy(FileWrapper::from_writer(Encoder { buffer: Vec::new(), out: out.clone() }))?;
+    let bytes = std::mem::take(&mut *out.lock().unwrap());
+    Ok(bytes.into_boxed_slice())
+}
+
+/// Reads the key and value length of the next entry of a `LazyType::Map` (or `None` at the end of the map)
+/// 
+/// `remaining` is the amount of bytes left in the map, which the entry is taken out of
+/// (so a corrupt length is rejected with `LDBError::InvalidValue` instead of being allocated)
+fn next_entry(wrapper: &mut FileWrapper, remaining: &mut u64) -> Result<Option<(String, usize)>, LDBError> {
+    let len = match wrapper.read_opt(4)? {
+        Some(x) => u32::from_be_bytes(x.as_ref().try_into().unwrap()),
+        None => return Ok(None),
+    };
+    *remaining = take(*remaining, 4 + len as u64, "key")?;
+    let bytes = wrapper.read(len as usize)?;
+    let key = match String::from_utf8(bytes.to_vec()) {
+        Ok(x) => x,
+        Err(_) => return Err(LDBError::InvalidUTF8String(bytes)),
+    };
+    let len = u64::from_be_bytes(wrapper.read(8)?.as_ref().try_into().unwrap());
+    *remaining = take(remaining.saturating_sub(8), len, "value")?;
+    Ok(Some((key, len as usize)))
+}
+
+/// Takes the length of part of an entry out of the amount of bytes left in a map
+fn take(remaining: u64, len: u64, part: &str) -> Result<u64, LDBError> {
+    match remaining.checked_sub(len) {
+        Some(x) => Ok(x),
+        None => Err(LDBError::InvalidValue(LazyType::Map, format!("{part} of '{len}' bytes is longer than the data"))),
+    }
+}
+
+impl LazyMap {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts a value at a key, replacing the value that was there (if any)
+    pub fn insert(&mut self, key: impl Into<String>, value: impl ToLazy) -> Result<(), LDBError> {
+        let key = key.into();
+        let bytes = encode(value)?;
+        match self.entries.iter_mut().find(|(k, _)| *k == key) {
+            Some(entry) => entry.1 = bytes,
+            None => self.entries.push((key, bytes)),
+        }
+        Ok(())
+    }
+
+    /// Gets the value at a key as `LazyData`
+    pub fn get(&self, key: &str) -> Result<Option<LazyData>, LDBError> {
+        match self.entries.iter().find(|(k, _)| k == key) {
+            Some((k, bytes)) => Ok(Some(LazyData::from_bytes(k, bytes.to_vec())?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Removes the value at a key, returning if there was one
+    pub fn remove(&mut self, key: &str) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|(k, _)| k != key);
+        self.entries.len() != len
+    }
+
+    #[inline]
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.entries.iter().any(|(k, _)| k == key)
+    }
+
+    /// The keys of the map in the order they were inserted
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(k, _)| k.as_str())
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Copies the contents of a `LazyContainer` into a map (nested containers become nested maps)
+    /// 
+    /// The conversion is one-way for nested containers, as `LazyMap::write_to` writes nested maps as `LazyData`
+    pub fn from_container(container: &LazyContainer) -> Result<Self, LDBError> {
+        let mut map = Self::new();
+        for (key, is_dir) in container.entries()? {
+            let bytes = if is_dir {
+                encode(Self::from_container(&container.read_container(&key)?)?)?
+            } else {
+                let reader = unwrap_result!((container.backend().open_reader(&container.path().join(&key))) err => LDBError::IOError(err));
+                reader.read_to_end()?
+            };
+            map.entries.push((key, bytes));
+        }
+        Ok(map)
+    }
+
+    /// Writes each entry of the map as `LazyData` within a `LazyContainer` (nested maps stay as `LazyData`)
+    /// 
+    /// Returns `LDBError::InvalidKey` (before writing anything) if a key is a path or starts with `.`
+    pub fn write_to(&self, container: &LazyContainer) -> Result<(), LDBError> {
+        self.check_keys()?;
+        for (key, bytes) in self.entries.iter() {
+            let mut file = container.data_writer(key)?;
+            file.write(bytes)?;
+            unwrap_result!((file.finish()) err => LDBError::IOError(err));
+        }
+        Ok(())
+    }
+
+    /// Lists the links within the map as where they'd be within a container at a path and what they point to
+    pub(crate) fn links(&self, path: &Path) -> Result<Vec<(PathBuf, PathBuf)>, LDBError> {
+        let mut links = Vec::new();
+        for (key, bytes) in self.entries.iter() {
+            if bytes.first() != Some(&LazyType::Link.into()) { continue };
+            links.push((path.join(key), LazyData::from_bytes(key, bytes.to_vec())?.collect_link_path()?));
+        }
+        Ok(links)
+    }
+
+    /// Returns `LDBError::InvalidKey` if any key can't be a key of a `LazyContainer`
+    pub(crate) fn check_keys(&self) -> Result<(), LDBError> {
+        for (key, _) in self.entries.iter() { crate::lazy_container::check_key(key)? };
+        Ok(())
+    }
+
+    /// Writes the entries of the map (after the type byte)
+    pub(crate) fn write_entries(&self, file: &mut FileWrapper) -> Result<(), LDBError> {
+        for (key, bytes) in self.entries.iter() {
+            file.write(&(key.len() as u32).to_be_bytes())?;
+            file.write(key.as_bytes())?;
+            file.write(&(bytes.len() as u64).to_be_bytes())?;
+            file.write(bytes)?;
+        }
+        Ok(())
+    }
+}
+
+impl LazyData {
+    /// Loads `LazyData` from the bytes of a `LazyData` file in memory
+    pub(crate) fn from_bytes(path: impl AsRef<Path>, bytes: Vec<u8>) -> Result<Self, LDBError> {
+        let mut wrapper = FileWrapper::from_reader(Cursor::new(bytes));
+        let lazy_type =
+            LazyType::try_from(wrapper.read(1)?[0])?;
+
+        Ok(Self {
+            path: path.as_ref().to_path_buf(),
+            lazy_type,
+            wrapper,
+            start: 0,
+        })
+    }
+
+    /// Checks that the `LazyData` is a `LazyType::Map` and seeks to it's first entry, returning the length of it's entries
+    fn map_entries(&mut self) -> Result<u64, LDBError> {
+        if self.lazy_type != LazyType::Map { return Err(LDBError::IncorrectType(self.lazy_type, String::from("LazyType::Map"))) };
+        let remaining = self.data_len()?.saturating_sub(1);
+        self.seek_data(1)?;
+        Ok(remaining)
+    }
+
+    /// Reads every entry of a `LazyType::Map`
+    pub(crate) fn read_map(mut self) -> Result<LazyMap, LDBError> {
+        let mut remaining = self.map_entries()?;
+        let mut map = LazyMap::new();
+        while let Some((key, len)) = next_entry(&mut self.wrapper, &mut remaining)? {
+            map.entries.push((key, self.wrapper.read(len)?));
+        }
+        Ok(map)
+    }
+
+    /// Reads only the value at a key of a `LazyType::Map` (seeking past the values before it)
+    ///
+    /// Returns `LDBError::IncorrectType` if the LazyData type is not `LazyType::Map`
+    pub fn map_get(mut self, key: &str) -> Result<Option<LazyData>, LDBError> {
+        let mut remaining = self.map_entries()?;
+        while let Some((k, len)) = next_entry(&mut self.wrapper, &mut remaining)? {
+            if k == key { return Ok(Some(LazyData::from_bytes(k, self.wrapper.read(len)?.into_vec())?)) };
+            unwrap_result!((self.wrapper.skip(len as i64)) err => LDBError::IOError(err));
+        }
+        Ok(None)
+    }
+
+    /// Reads only the keys of a `LazyType::Map` (seeking past it's values)
+    ///
+    /// Returns `LDBError::IncorrectType` if the LazyData type is not `LazyType::Map`
+    pub fn map_keys(mut self) -> Result<Vec<String>, LDBError> {
+        let mut remaining = self.map_entries()?;
+        let mut keys = Vec::new();
+        while let Some((key, len)) = next_entry(&mut self.wrapper, &mut remaining)? {
+            unwrap_result!((self.wrapper.skip(len as i64)) err => LDBError::IOError(err));
+            keys.push(key);
+        }
+        Ok(keys)
+    }
+}
@@ -0,0 +1,167 @@
+use super::*;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use memmap2::Mmap;
+
+/// The bytes of a `LazyData` file that are memory-mapped instead of read into memory
+/// 
+/// `LazyData` that isn't stored on the filesystem (like in a `MemoryBackend`) is read into memory instead.
+/// 
+/// **Note:** writes replace files rather than modifying them, so the mapped bytes don't change underneath you;
+/// the exceptions are the in-place array operations of `LazyContainer` (like `array_set` and `truncate_array`),
+/// writing with a non-atomic `FileWrapper::new_writer` and other processes, which is why mapping is `unsafe`
+pub struct MappedBytes {
+    inner: MappedInner,
+    offset: usize,
+}
+
+enum MappedInner {
+    Mapped(Mmap),
+    Owned(Box<[u8]>),
+}
+
+impl Deref for MappedBytes {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        match &self.inner {
+            MappedInner::Mapped(x) => &x[self.offset..],
+            MappedInner::Owned(x) => x,
+        }
+    }
+}
+
+impl AsRef<[u8]> for MappedBytes {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+/// A view of a memory-mapped `LazyType::Array` whose elements are converted as they're accessed (without copying the array)
+pub struct MappedArray<T: FixedArrayItem> {
+    bytes: MappedBytes,
+    _marker: PhantomData<T>,
+}
+
+impl<T: FixedArrayItem> MappedArray<T> {
+    /// The amount of elements in the array
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.bytes.len() / T::SIZE
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets the element at an index (if it's in bounds)
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<T> {
+        let start = index.checked_mul(T::SIZE)?;
+        self.bytes.get(start..start + T::SIZE).map(T::from_bytes)
+    }
+
+    /// Iterates over the elements of the array
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        self.bytes.chunks_exact(T::SIZE).map(T::from_bytes)
+    }
+
+    /// The raw (big-endian) bytes of the array's elements
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl Deref for MappedArray<u8> {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+macro_rules! map_array {
+    ($($name:ident => $type:ty;)*) => {$(
+        /// Memory-maps the `LazyData` as an array of values of a single type (see `LazyData::map_array`)
+        /// 
+        /// # Safety
+        /// The same as `LazyData::map_array`
+        #[inline]
+        pub unsafe fn $name(self) -> Result<MappedArray<$type>, LDBError> {
+            // SAFETY: upheld by the caller
+            unsafe { self.map_array() }
+        }
+    )*};
+}
+
+impl LazyData {
+    /// Memory-maps the rest of the file after `offset` bytes (which must have already been read from the wrapper)
+    /// 
+    /// # Safety
+    /// The file must not be modified in place or truncated while the mapping exists
+    unsafe fn map_rest(self, offset: usize) -> Result<MappedBytes, LDBError> {
+        let inner = match self.wrapper {
+            // SAFETY: upheld by the caller
+            FileWrapper::Reader(reader) => MappedInner::Mapped(
+                unwrap_result!((unsafe { Mmap::map(reader.get_ref()) }) err => LDBError::IOError(err))
+            ),
+            wrapper => MappedInner::Owned(wrapper.read_to_end()?),
+        };
+        Ok(MappedBytes { inner, offset: self.start as usize + offset })
+    }
+
+    /// Memory-maps the `LazyData` as binary instead of reading it into memory
+    /// 
+    /// Returns `LDBError::IncorrectType` if the LazyData type is not `LazyType::Binary`
+    /// 
+    /// # Safety
+    /// The file must not be modified in place or truncated while the `MappedBytes` exist:
+    /// no in-place array operations of `LazyContainer` (like `array_set` and `truncate_array`) on it,
+    /// no writing to it with a non-atomic `FileWrapper::new_writer` and no modifying it from other processes
+    pub unsafe fn map_binary(self) -> Result<MappedBytes, LDBError> {
+        if self.lazy_type != LazyType::Binary { return Err(LDBError::IncorrectType(self.lazy_type, String::from("LazyType::Binary"))) };
+        // SAFETY: upheld by the caller
+        unsafe { self.map_rest(1) }
+    }
+
+    /// Memory-maps the `LazyData` as an array of values of a single type instead of reading it into memory
+    /// 
+    /// Returns `LDBError::IncorrectType` if the LazyData type is not the correct array type
+    /// 
+    /// # Safety
+    /// The same as `LazyData::map_binary`, for as long as the `MappedArray` exists
+    pub unsafe fn map_array<T: FixedArrayItem>(mut self) -> Result<MappedArray<T>, LDBError> {
+        if self.lazy_type != LazyType::Array { return Err(LDBError::IncorrectType(self.lazy_type, String::from("LazyType::Array"))) };
+
+        // Read array-type
+        let array_type =
+            LazyType::try_from(self.wrapper.read(1)?[0])?;
+        if array_type != T::LAZY_TYPE { return Err(LDBError::IncorrectType(array_type, format!("LazyType::{:?}", T::LAZY_TYPE))) };
+
+        Ok(MappedArray {
+            // SAFETY: upheld by the caller
+            bytes: unsafe { self.map_rest(2)? },
+            _marker: PhantomData,
+        })
+    }
+
+    map_array! {
+        map_u8_array => u8;
+        map_u16_array => u16;
+        map_u32_array => u32;
+        map_u64_array => u64;
+        map_u128_array => u128;
+        map_i8_array => i8;
+        map_i16_array => i16;
+        map_i32_array => i32;
+        map_i64_array => i64;
+        map_i128_array => i128;
+        map_f32_array => f32;
+        map_f64_array => f64;
+    }
+}
@@ -1,4 +1,24 @@
 use super::*;
+use std::time::Duration;
+
+/// A type that can be collected from a `LazyData` file
+pub trait FromLazy: Sized {
+    /// Collects the `LazyData` as `Self`
+    /// 
+    /// Returns `LDBError::IncorrectType` if the `LazyType` of the data doesn't match
+    fn from_lazy(data: LazyData) -> Result<Self, LDBError>;
+}
+
+macro_rules! from_lazy {
+    ($(($func:ident) $type:ty;)*) => {$(
+        impl FromLazy for $type {
+            #[inline]
+            fn from_lazy(data: LazyData) -> Result<Self, LDBError> {
+                data.$func()
+            }
+        }
+    )*};
+}
 
 macro_rules! incorrect_type {
     ($original:expr, $expected:pat) => {
@@ -106,6 +126,18 @@ macro_rules! collect_array {
 }
 
 impl LazyData {
+    /// ### Expensive Action
+    /// ( Loads the entire file's data into memory )
+    /// 
+    /// ---
+    /// Collects the `LazyData` as any type that implements `FromLazy`
+    /// 
+    /// This is the generic version of the `collect_<type>` functions
+    #[inline]
+    pub fn collect<T: FromLazy>(self) -> Result<T, LDBError> {
+        T::from_lazy(self)
+    }
+
     /// ### Expensive Action
     /// ( Reads all of the contents of the file and stores it on the heap )
     /// 
@@ -166,6 +198,41 @@ impl LazyData {
     collect_array!((collect_f32_array, 4) f32 = F32);
     collect_array!((collect_f64_array, 8) f64 = F64);
 
+    /// ### Expensive Action
+    /// ( Loads the entire file's data into memory  )
+    /// 
+    /// ---
+    /// Collects the `LazyData` as an array of strings
+    /// 
+    /// Returns `LDBError::IncorrectType` if the LazyData type is not an array of strings
+    pub fn collect_string_array(self) -> Result<Box<[String]>, LDBError> {
+        self.var_array_iter::<String>()?.collect()
+    }
+
+    /// ### Expensive Action
+    /// ( Loads the entire file's data into memory  )
+    /// 
+    /// ---
+    /// Collects the `LazyData` as an array of binaries
+    /// 
+    /// Returns `LDBError::IncorrectType` if the LazyData type is not an array of binaries
+    pub fn collect_binary_array(self) -> Result<Box<[Box<[u8]>]>, LDBError> {
+        self.var_array_iter::<Box<[u8]>>()?.collect()
+    }
+
+    /// ### Expensive Action
+    /// ( Loads the entire file's data into memory  )
+    /// 
+    /// ---
+    /// Collects the `LazyData` as an array of booleans
+    /// 
+    /// Returns `LDBError::IncorrectType` if the LazyData type is not an array of booleans
+    pub fn collect_bool_array(mut self) -> Result<Box<[bool]>, LDBError> {
+        let len = self.bool_array_len()?;
+        let bytes = self.wrapper.read_to_end()?;
+        Ok((0..len).map(|i| bytes[i / 8] & (0x80 >> (i % 8)) != 0).collect())
+    }
+
     /* Floating point numbers */
 
     /// ### Expensive Action
@@ -233,24 +300,211 @@ impl LazyData {
         }
     }
 
+    /// Reads all of the (fixed-width) data of a `LazyType`, checking that it's the correct length
+    fn read_exact_data<const N: usize>(self, lazy_type: LazyType) -> Result<[u8; N], LDBError> {
+        if self.lazy_type != lazy_type { return Err(LDBError::IncorrectType(self.lazy_type, format!("LazyType::{lazy_type:?}"))) };
+        let bytes = self.wrapper.read_to_end()?;
+        match <[u8; N]>::try_from(bytes.as_ref()) {
+            Ok(x) => Ok(x),
+            Err(_) => Err(LDBError::InvalidNumberByteLength(bytes.len() as u8, format!("LazyType::{lazy_type:?}"))),
+        }
+    }
+
     /// ### Inexpensive Action
-    /// ( Loads `LazyData` specified at path )
+    /// ( Reads 12 bytes )
     /// 
     /// ---
-    /// Collects the `LazyData` as a path and converts that into another `LazyData`.
-    pub fn collect_link(self, database: LazyDB) -> Result<LazyData, LDBError> {
-        incorrect_type!(self.lazy_type, LazyType::Link);
+    /// Collects the `LazyData` as a `LazyTimestamp`
+    /// 
+    /// Returns `LDBError::IncorrectType` if the LazyData type is not `LazyType::Timestamp`
+    pub fn collect_timestamp(self) -> Result<LazyTimestamp, LDBError> {
+        let bytes = self.read_exact_data::<12>(LazyType::Timestamp)?;
+        let secs = i64::from_be_bytes(bytes[..8].try_into().unwrap());
+        let nanos = u32::from_be_bytes(bytes[8..].try_into().unwrap());
+        LazyTimestamp::new(secs, nanos)
+            .ok_or(LDBError::InvalidValue(LazyType::Timestamp, format!("'{nanos}' nanoseconds is more than a second")))
+    }
 
-        // Loads string as a path
-        // Expensive and best to be avoided if possible
-        let bytes = self.wrapper.read_to_end()?;
-        
-        let string = if let Ok(x) = String::from_utf8(bytes.to_vec()) {
-            x
-        } else {
-            return Err(LDBError::InvalidUTF8String(bytes))
+    /// ### Inexpensive Action
+    /// ( Reads 12 bytes )
+    /// 
+    /// ---
+    /// Collects the `LazyData` as a `Duration`
+    /// 
+    /// Returns `LDBError::IncorrectType` if the LazyData type is not `LazyType::Duration`
+    pub fn collect_duration(self) -> Result<Duration, LDBError> {
+        let bytes = self.read_exact_data::<12>(LazyType::Duration)?;
+        let secs = u64::from_be_bytes(bytes[..8].try_into().unwrap());
+        let nanos = u32::from_be_bytes(bytes[8..].try_into().unwrap());
+        if nanos >= 1_000_000_000 { return Err(LDBError::InvalidValue(LazyType::Duration, format!("'{nanos}' nanoseconds is more than a second"))) };
+        Ok(Duration::new(secs, nanos))
+    }
+
+    /// ### Inexpensive Action
+    /// ( Reads 16 bytes )
+    /// 
+    /// ---
+    /// Collects the `LazyData` as a `LazyUuid`
+    /// 
+    /// Returns `LDBError::IncorrectType` if the LazyData type is not `LazyType::Uuid`
+    #[inline]
+    pub fn collect_uuid(self) -> Result<LazyUuid, LDBError> {
+        Ok(LazyUuid(self.read_exact_data::<16>(LazyType::Uuid)?))
+    }
+
+    /// ### Inexpensive Action
+    /// ( Reads 4 bytes )
+    /// 
+    /// ---
+    /// Collects the `LazyData` as a `char`
+    /// 
+    /// Returns `LDBError::IncorrectType` if the LazyData type is not `LazyType::Char`
+    pub fn collect_char(self) -> Result<char, LDBError> {
+        let code = u32::from_be_bytes(self.read_exact_data::<4>(LazyType::Char)?);
+        char::from_u32(code)
+            .ok_or(LDBError::InvalidValue(LazyType::Char, format!("'{code:#x}' is not a unicode scalar value")))
+    }
+
+    /// ### Inexpensive Action
+    /// ( Reads 20 bytes )
+    /// 
+    /// ---
+    /// Collects the `LazyData` as a `LazyDecimal`
+    /// 
+    /// Returns `LDBError::IncorrectType` if the LazyData type is not `LazyType::Decimal`
+    /// and `LDBError::InvalidValue` if it's scale is more than `LazyDecimal::MAX_SCALE`
+    pub fn collect_decimal(self) -> Result<LazyDecimal, LDBError> {
+        let bytes = self.read_exact_data::<20>(LazyType::Decimal)?;
+        LazyDecimal {
+            mantissa: i128::from_be_bytes(bytes[..16].try_into().unwrap()),
+            scale: u32::from_be_bytes(bytes[16..].try_into().unwrap()),
+        }.check()
+    }
+
+    /// ### Inexpensive Action
+    /// ( Reads the flag and type of the value )
+    /// 
+    /// ---
+    /// Collects the `LazyData` as an option of it's value (which is also `LazyData`)
+    /// 
+    /// Returns `LDBError::IncorrectType` if the LazyData type is not `LazyType::Option`
+    pub fn collect_option(mut self) -> Result<Option<LazyData>, LDBError> {
+        incorrect_type!(self.lazy_type, LazyType::Option);
+        match self.wrapper.read(1)?[0] {
+            0 => Ok(None),
+            _ => Ok(Some(self.nested()?)),
+        }
+    }
+
+    /// ### Inexpensive Action
+    /// ( Reads the variant's index, name and payload type )
+    /// 
+    /// ---
+    /// Collects the `LazyData` as a variant of an enum, with it's payload as `LazyData`
+    /// 
+    /// Returns `LDBError::IncorrectType` if the LazyData type is not `LazyType::Enum`
+    pub fn collect_enum(mut self) -> Result<LazyVariant, LDBError> {
+        incorrect_type!(self.lazy_type, LazyType::Enum);
+        let index = u32::from_be_bytes(self.wrapper.read(4)?.as_ref().try_into().unwrap());
+        let len = u32::from_be_bytes(self.wrapper.read(4)?.as_ref().try_into().unwrap());
+        // The name can't be longer than what's left of the data (so a corrupt length isn't allocated)
+        if len as u64 > self.data_len()?.saturating_sub(9) { return Err(LDBError::InvalidValue(LazyType::Enum, format!("name of '{len}' bytes is longer than the data"))) };
+        self.seek_data(9)?;
+        let bytes = self.wrapper.read(len as usize)?;
+
+        let name = match String::from_utf8(bytes.to_vec()) {
+            Ok(x) => x,
+            Err(_) => return Err(LDBError::InvalidUTF8String(bytes)),
         };
 
-        database.as_container()?.read_data(string)
+        Ok(LazyVariant {
+            index,
+            name,
+            payload: self.nested()?,
+        })
     }
-}
\ No newline at end of file
+
+    /// ### Expensive Action
+    /// ( Loads the entire file's data into memory  )
+    /// 
+    /// ---
+    /// Collects the `LazyData` as a `LazyMap` (use `LazyData::map_get` to read only one value)
+    /// 
+    /// Returns `LDBError::IncorrectType` if the LazyData type is not `LazyType::Map`
+    #[inline]
+    pub fn collect_map(self) -> Result<LazyMap, LDBError> {
+        self.read_map()
+    }
+
+    /// Collects the `LazyData` as the path a link points to (without resolving it)
+    ///
+    /// Returns `LDBError::IncorrectType` if the LazyData type is not `LazyType::Link`
+    pub fn collect_link_path(self) -> Result<PathBuf, LDBError> {
+        incorrect_type!(self.lazy_type, LazyType::Link);
+        let bytes = self.wrapper.read_to_end()?;
+        match String::from_utf8(bytes.to_vec()) {
+            Ok(x) => Ok(PathBuf::from(x)),
+            Err(_) => Err(LDBError::InvalidUTF8String(bytes)),
+        }
+    }
+
+    /// ### Inexpensive Action
+    /// ( Loads `LazyData` specified at path )
+    /// 
+    /// ---
+    /// Collects the `LazyData` as a link and follows it (and any links it points to) to another `LazyData` (see `LazyDB::resolve_link`)
+    #[inline]
+    pub fn collect_link(self, database: &LazyDB) -> Result<LazyData, LDBError> {
+        database.resolve_link(self)?.into_data()
+    }
+}
+from_lazy! {
+    (collect_bool) bool;
+    (collect_u8) u8;
+    (collect_u16) u16;
+    (collect_u32) u32;
+    (collect_u64) u64;
+    (collect_u128) u128;
+    (collect_i8) i8;
+    (collect_i16) i16;
+    (collect_i32) i32;
+    (collect_i64) i64;
+    (collect_i128) i128;
+    (collect_f32) f32;
+    (collect_f64) f64;
+    (collect_string) String;
+    (collect_timestamp) LazyTimestamp;
+    (collect_duration) Duration;
+    (collect_uuid) LazyUuid;
+    (collect_char) char;
+    (collect_decimal) LazyDecimal;
+    (collect_map) LazyMap;
+}
+
+impl FromLazy for () {
+    fn from_lazy(data: LazyData) -> Result<Self, LDBError> {
+        incorrect_type!(data.lazy_type, LazyType::Void);
+        Ok(())
+    }
+}
+
+impl<T: LazyArrayItem> FromLazy for Box<[T]> {
+    #[inline]
+    fn from_lazy(data: LazyData) -> Result<Self, LDBError> {
+        T::collect_array(data)
+    }
+}
+
+impl<T: LazyArrayItem> FromLazy for Vec<T> {
+    #[inline]
+    fn from_lazy(data: LazyData) -> Result<Self, LDBError> {
+        Ok(T::collect_array(data)?.into_vec())
+    }
+}
+
+impl<T: FromLazy> FromLazy for Option<T> {
+    #[inline]
+    fn from_lazy(data: LazyData) -> Result<Self, LDBError> {
+        data.collect_option()?.map(T::from_lazy).transpose()
+    }
+}
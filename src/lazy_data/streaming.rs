@@ -0,0 +1,114 @@
+use super::*;
+use std::io::{self, Read, Write, Seek, SeekFrom};
+
+/// A reader of the bytes of a `LazyData` after it's type byte (see `LazyData::into_reader`)
+///
+/// Positions are relative to the start of the value (straight after the type byte)
+pub struct LazyReader {
+    wrapper: FileWrapper,
+    /// Where the value starts within the file
+    start: u64,
+}
+
+impl Read for LazyReader {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+        self.wrapper.reader().read(buf)
+    }
+}
+
+impl Seek for LazyReader {
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64, io::Error> {
+        let pos = match pos {
+            SeekFrom::Start(x) => SeekFrom::Start(self.start + x),
+            SeekFrom::Current(x) => {
+                let current = self.wrapper.seek(SeekFrom::Current(0))?;
+                if (current as i64 + x) < self.start as i64 { return Err(io::Error::new(io::ErrorKind::InvalidInput, "cannot seek before the start of the value")) };
+                SeekFrom::Current(x)
+            },
+            SeekFrom::End(x) => {
+                let end = self.wrapper.seek(SeekFrom::End(0))?;
+                if (end as i64 + x) < self.start as i64 { return Err(io::Error::new(io::ErrorKind::InvalidInput, "cannot seek before the start of the value")) };
+                SeekFrom::End(x)
+            },
+        };
+        Ok(self.wrapper.seek(pos)? - self.start)
+    }
+}
+
+/// A writer that streams the value of a `LazyType::Binary` or `LazyType::String` into a `LazyData` file
+///
+/// The value only replaces the existing data (if any) once the writer is finished with `LazyWriter::finish`;
+/// if it's dropped before then, nothing is written
+pub struct LazyWriter {
+    wrapper: FileWrapper,
+    /// The trailing bytes of an incomplete utf-8 character (for strings only)
+    utf8: Option<Vec<u8>>,
+}
+
+impl LazyWriter {
+    /// Writes the header of a `LazyData` file with a streamed value
+    pub(crate) fn new(mut wrapper: FileWrapper, lazy_type: LazyType) -> Result<Self, LDBError> {
+        wrapper.write(&[lazy_type.into()])?;
+        Ok(Self {
+            wrapper,
+            utf8: if lazy_type == LazyType::String { Some(Vec::new()) } else { None },
+        })
+    }
+
+    /// Finishes writing the value and replaces the existing data with it
+    ///
+    /// Returns `LDBError::InvalidUTF8String` if a streamed string ends part-way through a character
+    pub fn finish(self) -> Result<(), LDBError> {
+        if let Some(x) = self.utf8 {
+            if !x.is_empty() { return Err(LDBError::InvalidUTF8String(x.into_boxed_slice())) };
+        }
+        unwrap_result!((self.wrapper.finish()) err => LDBError::IOError(err));
+        Ok(())
+    }
+
+    /// Checks that the bytes written to a string are valid utf-8 (characters may be split between writes)
+    fn check_utf8(carry: &mut Vec<u8>, mut buf: &[u8]) -> Result<(), io::Error> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "stream did not contain valid UTF-8");
+
+        // Complete the character left over from the last write
+        if let Some(first) = carry.first() {
+            let width = match first { 0xF0.. => 4, 0xE0.. => 3, _ => 2 };
+            let needed = (width - carry.len()).min(buf.len());
+            carry.extend_from_slice(&buf[..needed]);
+            buf = &buf[needed..];
+            if carry.len() < width { return Ok(()) };
+            if std::str::from_utf8(carry).is_err() { return Err(invalid()) };
+            carry.clear();
+        }
+
+        match std::str::from_utf8(buf) {
+            Ok(_) => Ok(()),
+            Err(e) if e.error_len().is_none() => { carry.extend_from_slice(&buf[e.valid_up_to()..]); Ok(()) },
+            Err(_) => Err(invalid()),
+        }
+    }
+}
+
+impl Write for LazyWriter {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, io::Error> {
+        if let Some(carry) = &mut self.utf8 { Self::check_utf8(carry, buf)? };
+        self.wrapper.writer().write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<(), io::Error> {
+        self.wrapper.writer().flush()
+    }
+}
+
+impl LazyData {
+    /// Gets a reader (that can seek) of the value of the `LazyData`, positioned straight after it's type byte
+    ///
+    /// Useful for reading large `LazyType::Binary` or `LazyType::String` values incrementally
+    #[inline]
+    pub fn into_reader(self) -> LazyReader {
+        LazyReader { start: self.start + 1, wrapper: self.wrapper }
+    }
+}
@@ -0,0 +1,186 @@
+use super::*;
+use std::ops::Range;
+
+/// A multi-dimensional array of fixed-width numbers in row-major order, stored as a `LazyType::Tensor`
+///
+/// ## Layout
+/// After the type byte, the element type (`u8`), the amount of dimensions (`u8`), each dimension (`u64`) and then the elements;
+/// the numbers are big-endian like a `LazyType::Array`
+#[derive(Debug, Clone, PartialEq)]
+pub struct LazyTensor<T: FixedArrayItem> {
+    pub shape: Box<[usize]>,
+    pub data: Box<[T]>,
+}
+
+impl<T: FixedArrayItem> LazyTensor<T> {
+    /// Constructs a new `LazyTensor`, returning `LDBError::InvalidValue` if the amount of elements doesn't match the shape
+    pub fn new(shape: impl Into<Box<[usize]>>, data: impl Into<Box<[T]>>) -> Result<Self, LDBError> {
+        let (shape, data) = (shape.into(), data.into());
+        check_shape(&shape, data.len())?;
+        Ok(Self { shape, data })
+    }
+
+    /// Gets the element at an index (with one coordinate for each dimension)
+    pub fn get(&self, index: &[usize]) -> Option<T> {
+        if index.len() != self.shape.len() { return None };
+        let mut offset = 0;
+        for (i, dim) in index.iter().zip(self.shape.iter()) {
+            if i >= dim { return None };
+            offset = offset * dim + i;
+        }
+        self.data.get(offset).copied()
+    }
+}
+
+impl<T: FixedArrayItem> ToLazy for LazyTensor<T> {
+    #[inline]
+    fn to_lazy(&self, file: FileWrapper) -> Result<(), LDBError> {
+        LazyData::new_tensor(file, &self.shape, &self.data)
+    }
+}
+
+impl<T: FixedArrayItem> FromLazy for LazyTensor<T> {
+    #[inline]
+    fn from_lazy(data: LazyData) -> Result<Self, LDBError> {
+        data.collect_tensor()
+    }
+}
+
+/// Multiplies the dimensions of a shape (and the size of an element), returning `LDBError::InvalidValue` if it overflows
+fn checked_len(shape: &[usize], size: usize) -> Result<usize, LDBError> {
+    if shape.contains(&0) { return Ok(0) };
+    shape.iter().try_fold(size, |len, dim| len.checked_mul(*dim))
+        .ok_or_else(|| LDBError::InvalidValue(LazyType::Tensor, format!("shape {shape:?} is too large")))
+}
+
+/// Returns `LDBError::InvalidValue` if a shape has too many dimensions or doesn't hold `len` elements
+fn check_shape(shape: &[usize], len: usize) -> Result<(), LDBError> {
+    if shape.len() > u8::MAX as usize { return Err(LDBError::InvalidValue(LazyType::Tensor, format!("'{}' dimensions is more than {}", shape.len(), u8::MAX))) };
+    if checked_len(shape, 1)? != len { return Err(LDBError::InvalidValue(LazyType::Tensor, format!("shape {shape:?} doesn't hold '{len}' elements"))) };
+    Ok(())
+}
+
+macro_rules! tensor {
+    ($(($new:ident, $collect:ident) $type:ty;)*) => {$(
+        /// Creates a new `LazyData` file with a tensor of a single type (see `LazyData::new_tensor`)
+        #[inline]
+        pub fn $new(file: FileWrapper, shape: &[usize], data: &[$type]) -> Result<(), LDBError> {
+            Self::new_tensor(file, shape, data)
+        }
+
+        /// ### Expensive Action
+        /// ( Loads the entire file's data into memory  )
+        ///
+        /// ---
+        /// Collects the `LazyData` as a tensor of a single type (see `LazyData::collect_tensor`)
+        #[inline]
+        pub fn $collect(self) -> Result<LazyTensor<$type>, LDBError> {
+            self.collect_tensor()
+        }
+    )*};
+}
+
+impl LazyData {
+    /// Creates a new `LazyData` file with a tensor (a multi-dimensional array) in row-major order
+    ///
+    /// Returns `LDBError::InvalidValue` if the amount of elements doesn't match the shape
+    pub fn new_tensor<T: FixedArrayItem>(mut file: FileWrapper, shape: &[usize], data: &[T]) -> Result<(), LDBError> {
+        check_shape(shape, data.len())?;
+        file.write(&[LazyType::Tensor.into(), T::LAZY_TYPE.into(), shape.len() as u8])?;
+        for dim in shape.iter() { file.write(&(*dim as u64).to_be_bytes())? };
+
+        let mut bytes = Vec::with_capacity(checked_len(shape, T::SIZE)?);
+        for x in data { x.write_bytes(&mut bytes) };
+        file.write(&bytes)?;
+        unwrap_result!((file.finish()) err => LDBError::IOError(err));
+        Ok(())
+    }
+
+    /// Reads the shape of a `LazyType::Tensor` (checking it's element type), leaving the reader at it's first element
+    /// 
+    /// Returns `LDBError::InvalidValue` if the shape doesn't match the amount of data (so a corrupt shape isn't allocated)
+    fn tensor_header<T: FixedArrayItem>(&mut self) -> Result<Box<[usize]>, LDBError> {
+        if self.lazy_type != LazyType::Tensor { return Err(LDBError::IncorrectType(self.lazy_type, String::from("LazyType::Tensor"))) };
+        self.seek_data(1)?;
+        let header = self.wrapper.read(2)?;
+        let element_type = LazyType::try_from(header[0])?;
+        if element_type != T::LAZY_TYPE { return Err(LDBError::IncorrectType(element_type, format!("LazyType::{:?}", T::LAZY_TYPE))) };
+
+        let bytes = self.wrapper.read(header[1] as usize * 8)?;
+        let shape: Box<[usize]> = bytes.chunks_exact(8).map(|x| u64::from_be_bytes(x.try_into().unwrap()) as usize).collect();
+        let start = 3 + bytes.len() as u64;
+        let len = self.data_len()?.saturating_sub(start);
+        if checked_len(&shape, T::SIZE)? as u64 != len { return Err(LDBError::InvalidValue(LazyType::Tensor, format!("shape {shape:?} doesn't match the '{len}' bytes of data"))) };
+        self.seek_data(start)?;
+        Ok(shape)
+    }
+
+    /// Reads the elements of a shape of a tensor (from where the reader is)
+    fn read_elements<T: FixedArrayItem>(&mut self, shape: &[usize]) -> Result<Box<[T]>, LDBError> {
+        let bytes = self.wrapper.read(checked_len(shape, T::SIZE)?)?;
+        Ok(bytes.chunks_exact(T::SIZE).map(T::from_bytes).collect())
+    }
+
+    /// ### Expensive Action
+    /// ( Loads the entire file's data into memory  )
+    ///
+    /// ---
+    /// Collects the `LazyData` as a tensor (a multi-dimensional array) of a single type
+    ///
+    /// Returns `LDBError::IncorrectType` if the LazyData type is not the correct tensor type
+    pub fn collect_tensor<T: FixedArrayItem>(mut self) -> Result<LazyTensor<T>, LDBError> {
+        let shape = self.tensor_header::<T>()?;
+        let data = self.read_elements(&shape)?;
+        LazyTensor::new(shape, data)
+    }
+
+    /// Reads only the shape of a `LazyType::Tensor`
+    ///
+    /// Returns `LDBError::IncorrectType` if the LazyData type is not the correct tensor type
+    #[inline]
+    pub fn tensor_shape<T: FixedArrayItem>(&mut self) -> Result<Box<[usize]>, LDBError> {
+        self.tensor_header::<T>()
+    }
+
+    /// Reads only a range of the rows (along the first dimension) of a `LazyType::Tensor`, as a tensor of the same dimensions
+    ///
+    /// Returns `LDBError::IncorrectType` if the LazyData type is not the correct tensor type
+    /// and `LDBError::IndexOutOfBounds` if the range isn't within the tensor
+    pub fn tensor_rows<T: FixedArrayItem>(&mut self, rows: Range<usize>) -> Result<LazyTensor<T>, LDBError> {
+        let mut shape = self.tensor_header::<T>()?;
+        let Some(&len) = shape.first() else { return Err(LDBError::IndexOutOfBounds(rows.end, 0)) };
+        if rows.end > len { return Err(LDBError::IndexOutOfBounds(rows.end, len)) };
+
+        let rows = rows.start.min(rows.end)..rows.end;
+        let skip = checked_len(&[&[rows.start], &shape[1..]].concat(), T::SIZE)?;
+        unwrap_result!((self.wrapper.skip(skip as i64)) err => LDBError::IOError(err));
+        shape[0] = rows.len();
+        let data = self.read_elements(&shape)?;
+        Ok(LazyTensor { shape, data })
+    }
+
+    /// Reads only a row (along the first dimension) of a `LazyType::Tensor`, as a tensor of one less dimension
+    ///
+    /// Returns `LDBError::IncorrectType` if the LazyData type is not the correct tensor type
+    /// and `LDBError::IndexOutOfBounds` if the row isn't within the tensor
+    pub fn tensor_row<T: FixedArrayItem>(&mut self, row: usize) -> Result<LazyTensor<T>, LDBError> {
+        let tensor = self.tensor_rows::<T>(row..row.saturating_add(1)) // `usize::MAX` is still out of bounds
+            .map_err(|e| match e { LDBError::IndexOutOfBounds(_, len) => LDBError::IndexOutOfBounds(row, len), e => e })?;
+        Ok(LazyTensor { shape: tensor.shape[1..].into(), data: tensor.data })
+    }
+
+    tensor! {
+        (new_u8_tensor, collect_u8_tensor) u8;
+        (new_u16_tensor, collect_u16_tensor) u16;
+        (new_u32_tensor, collect_u32_tensor) u32;
+        (new_u64_tensor, collect_u64_tensor) u64;
+        (new_u128_tensor, collect_u128_tensor) u128;
+        (new_i8_tensor, collect_i8_tensor) i8;
+        (new_i16_tensor, collect_i16_tensor) i16;
+        (new_i32_tensor, collect_i32_tensor) i32;
+        (new_i64_tensor, collect_i64_tensor) i64;
+        (new_i128_tensor, collect_i128_tensor) i128;
+        (new_f32_tensor, collect_f32_tensor) f32;
+        (new_f64_tensor, collect_f64_tensor) f64;
+    }
+}
@@ -0,0 +1,267 @@
+use super::*;
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A point in time with nanosecond precision, stored as a `LazyType::Timestamp`
+///
+/// It's the (signed) seconds since the unix epoch and the nanoseconds after that second
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Default)]
+pub struct LazyTimestamp {
+    pub secs: i64,
+    /// Always less than a second (`1_000_000_000`)
+    pub nanos: u32,
+}
+
+impl LazyTimestamp {
+    pub const UNIX_EPOCH: Self = Self { secs: 0, nanos: 0 };
+
+    /// Constructs a new `LazyTimestamp`, returning `None` if the nanoseconds aren't less than a second
+    #[inline]
+    pub fn new(secs: i64, nanos: u32) -> Option<Self> {
+        if nanos < 1_000_000_000 { Some(Self { secs, nanos }) }
+        else { None }
+    }
+
+    /// The current time
+    #[inline]
+    pub fn now() -> Self {
+        SystemTime::now().into()
+    }
+
+    /// Converts the timestamp into a `SystemTime`, returning `None` if this platform can't represent it
+    pub fn to_system_time(self) -> Option<SystemTime> {
+        let nanos = Duration::from_nanos(self.nanos as u64);
+        if self.secs >= 0 {
+            UNIX_EPOCH.checked_add(Duration::from_secs(self.secs as u64))?.checked_add(nanos)
+        } else {
+            UNIX_EPOCH.checked_sub(Duration::from_secs(self.secs.unsigned_abs()))?.checked_add(nanos)
+        }
+    }
+}
+
+impl From<SystemTime> for LazyTimestamp {
+    fn from(time: SystemTime) -> Self {
+        match time.duration_since(UNIX_EPOCH) {
+            Ok(x) => Self { secs: x.as_secs() as i64, nanos: x.subsec_nanos() },
+            Err(e) => { // before the unix epoch
+                let x = e.duration();
+                let secs = -(x.as_secs() as i64);
+                if x.subsec_nanos() == 0 { Self { secs, nanos: 0 } }
+                else { Self { secs: secs - 1, nanos: 1_000_000_000 - x.subsec_nanos() } }
+            },
+        }
+    }
+}
+
+/// A 128-bit universally unique identifier, stored as a `LazyType::Uuid`
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Default)]
+pub struct LazyUuid(pub [u8; 16]);
+
+impl From<[u8; 16]> for LazyUuid {
+    #[inline]
+    fn from(bytes: [u8; 16]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<LazyUuid> for [u8; 16] {
+    #[inline]
+    fn from(uuid: LazyUuid) -> Self {
+        uuid.0
+    }
+}
+
+impl From<u128> for LazyUuid {
+    #[inline]
+    fn from(value: u128) -> Self {
+        Self(value.to_be_bytes())
+    }
+}
+
+impl From<LazyUuid> for u128 {
+    #[inline]
+    fn from(uuid: LazyUuid) -> Self {
+        u128::from_be_bytes(uuid.0)
+    }
+}
+
+/// An exact decimal number (`mantissa * 10^-scale`), stored as a `LazyType::Decimal`
+///
+/// Unlike floats, decimal fractions (like money) are represented exactly; it's formatted exactly with `Display`.
+/// Equality compares the mantissa and scale, so `1.50` isn't equal to `1.5`
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub struct LazyDecimal {
+    pub mantissa: i128,
+    /// The amount of digits after the decimal point (at most `LazyDecimal::MAX_SCALE`)
+    pub scale: u32,
+}
+
+impl LazyDecimal {
+    /// The largest scale of a decimal (the digits of an `i128`)
+    pub const MAX_SCALE: u32 = 38;
+
+    /// Constructs a new `LazyDecimal`, returning `LDBError::InvalidValue` if the scale is more than `LazyDecimal::MAX_SCALE`
+    #[inline]
+    pub fn new(mantissa: i128, scale: u32) -> Result<Self, LDBError> {
+        Self { mantissa, scale }.check()
+    }
+
+    /// Returns `LDBError::InvalidValue` if the scale is more than `LazyDecimal::MAX_SCALE`
+    pub(crate) fn check(self) -> Result<Self, LDBError> {
+        if self.scale > Self::MAX_SCALE { return Err(LDBError::InvalidValue(LazyType::Decimal, format!("scale '{}' is more than {}", self.scale, Self::MAX_SCALE))) };
+        Ok(self)
+    }
+
+    /// Converts the decimal into the closest `f64` (which may not be exact)
+    #[inline]
+    pub fn to_f64(self) -> f64 {
+        self.to_string().parse().unwrap() // `Display` always formats a valid float
+    }
+}
+
+impl fmt::Display for LazyDecimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // An invalid scale (the fields are public) is formatted with an exponent rather than padded out
+        if self.scale > Self::MAX_SCALE { return write!(f, "{}e-{}", self.mantissa, self.scale) };
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let scale = self.scale as usize;
+        let digits = format!("{digits:0>width$}", width = scale + 1); // at least one digit before the point
+        let (whole, fraction) = digits.split_at(digits.len() - scale);
+
+        if self.mantissa < 0 { write!(f, "-")? };
+        if fraction.is_empty() { write!(f, "{whole}") }
+        else { write!(f, "{whole}.{fraction}") }
+    }
+}
+
+/// A variant of an enum (a tagged union) collected from a `LazyType::Enum` (see `LazyData::new_enum`)
+#[derive(Debug)]
+pub struct LazyVariant {
+    pub index: u32,
+    pub name: String,
+    /// The variant's data (`LazyType::Void` for unit variants)
+    pub payload: LazyData,
+}
+
+impl ToLazy for SystemTime {
+    #[inline]
+    fn to_lazy(&self, file: FileWrapper) -> Result<(), LDBError> {
+        LazyData::new_timestamp(file, (*self).into())
+    }
+}
+
+impl FromLazy for SystemTime {
+    fn from_lazy(data: LazyData) -> Result<Self, LDBError> {
+        data.collect_timestamp()?.to_system_time()
+            .ok_or(LDBError::InvalidValue(LazyType::Timestamp, String::from("out of range for `SystemTime`")))
+    }
+}
+
+#[cfg(feature = "chrono")]
+mod chrono_impls {
+    use super::*;
+    use chrono::{DateTime, Utc};
+
+    impl From<DateTime<Utc>> for LazyTimestamp {
+        #[inline]
+        fn from(time: DateTime<Utc>) -> Self {
+            // Leap seconds are represented by chrono as nanoseconds past a second
+            Self { secs: time.timestamp(), nanos: time.timestamp_subsec_nanos().min(999_999_999) }
+        }
+    }
+
+    impl TryFrom<LazyTimestamp> for DateTime<Utc> {
+        type Error = LDBError;
+
+        #[inline]
+        fn try_from(time: LazyTimestamp) -> Result<Self, LDBError> {
+            DateTime::from_timestamp(time.secs, time.nanos)
+                .ok_or(LDBError::InvalidValue(LazyType::Timestamp, String::from("out of range for `chrono::DateTime`")))
+        }
+    }
+
+    impl ToLazy for DateTime<Utc> {
+        #[inline]
+        fn to_lazy(&self, file: FileWrapper) -> Result<(), LDBError> {
+            LazyData::new_timestamp(file, (*self).into())
+        }
+    }
+
+    impl FromLazy for DateTime<Utc> {
+        #[inline]
+        fn from_lazy(data: LazyData) -> Result<Self, LDBError> {
+            data.collect_timestamp()?.try_into()
+        }
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+mod rust_decimal_impls {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    impl From<Decimal> for LazyDecimal {
+        #[inline]
+        fn from(decimal: Decimal) -> Self {
+            Self { mantissa: decimal.mantissa(), scale: decimal.scale() }
+        }
+    }
+
+    impl TryFrom<LazyDecimal> for Decimal {
+        type Error = LDBError;
+
+        #[inline]
+        fn try_from(decimal: LazyDecimal) -> Result<Self, LDBError> {
+            Decimal::try_from_i128_with_scale(decimal.mantissa, decimal.scale)
+                .map_err(|e| LDBError::InvalidValue(LazyType::Decimal, format!("out of range for `rust_decimal::Decimal` ({e})")))
+        }
+    }
+
+    impl ToLazy for Decimal {
+        #[inline]
+        fn to_lazy(&self, file: FileWrapper) -> Result<(), LDBError> {
+            LazyData::new_decimal(file, (*self).into())
+        }
+    }
+
+    impl FromLazy for Decimal {
+        #[inline]
+        fn from_lazy(data: LazyData) -> Result<Self, LDBError> {
+            data.collect_decimal()?.try_into()
+        }
+    }
+}
+
+#[cfg(feature = "uuid")]
+mod uuid_impls {
+    use super::*;
+    use uuid::Uuid;
+
+    impl From<Uuid> for LazyUuid {
+        #[inline]
+        fn from(uuid: Uuid) -> Self {
+            Self(uuid.into_bytes())
+        }
+    }
+
+    impl From<LazyUuid> for Uuid {
+        #[inline]
+        fn from(uuid: LazyUuid) -> Self {
+            Uuid::from_bytes(uuid.0)
+        }
+    }
+
+    impl ToLazy for Uuid {
+        #[inline]
+        fn to_lazy(&self, file: FileWrapper) -> Result<(), LDBError> {
+            LazyData::new_uuid(file, (*self).into())
+        }
+    }
+
+    impl FromLazy for Uuid {
+        #[inline]
+        fn from_lazy(data: LazyData) -> Result<Self, LDBError> {
+            Ok(data.collect_uuid()?.into())
+        }
+    }
+}
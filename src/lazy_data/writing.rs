@@ -1,7 +1,31 @@
-use std::os::unix::prelude::OsStrExt;
+use std::time::Duration;
 
 use super::*;
 
+/// A type that can be written into a `LazyData` file along with it's `LazyType`
+pub trait ToLazy {
+    /// Writes the value into a `LazyData` file
+    fn to_lazy(&self, file: FileWrapper) -> Result<(), LDBError>;
+}
+
+macro_rules! to_lazy {
+    ($(($func:ident) $type:ty;)*) => {$(
+        impl ToLazy for $type {
+            #[inline]
+            fn to_lazy(&self, file: FileWrapper) -> Result<(), LDBError> {
+                LazyData::$func(file, *self)
+            }
+        }
+    )*};
+}
+
+/// Finishes writing a `LazyData` file (renaming it into place if it's atomic)
+#[inline]
+fn finish(file: FileWrapper) -> Result<(), LDBError> {
+    unwrap_result!((file.finish()) err => LDBError::IOError(err));
+    Ok(())
+}
+
 macro_rules! new_number {
     (($name:ident) $type:ty = $lazy_type:expr) => {
         /// Creates a new `LazyData` file with an unsigned integer and type
@@ -9,7 +33,7 @@ macro_rules! new_number {
             let bytes = value.to_be_bytes();
             file.write(&[$lazy_type.into()])?;
             file.write(&bytes)?;
-            Ok(())
+            finish(file)
         }
     };
 
@@ -19,7 +43,7 @@ macro_rules! new_number {
             let bytes = value.to_be_bytes();
             file.write(&[$lazy_type.into()])?;
             file.write(&bytes)?;
-            Ok(())
+            finish(file)
         }
     };
 }
@@ -33,16 +57,24 @@ macro_rules! new_array {
                 let bytes = i.to_be_bytes();
                 file.write(&bytes)?;
             }
-            Ok(())
+            finish(file)
         }
     }
 }
 
 impl LazyData {
+    /// Creates a new `LazyData` file from any value that implements `ToLazy`
+    /// 
+    /// This is the generic version of the `new_<type>` functions
+    #[inline]
+    pub fn write<T: ToLazy>(file: FileWrapper, value: T) -> Result<(), LDBError> {
+        value.to_lazy(file)
+    }
+
     /// Creates a new `LazyData` file with the type of `LazyType::Void`
     pub fn new_void(mut file: FileWrapper, _value: ()) -> Result<(), LDBError> {
         file.write(&[LazyType::Void.into()])?;
-        Ok(())
+        finish(file)
     }
 
     /// Creates a new `LazyData` file with a `String` value and type
@@ -50,7 +82,7 @@ impl LazyData {
         let bytes = value.as_bytes();
         file.write(&[LazyType::String.into()])?;
         file.write(bytes)?;
-        Ok(())
+        finish(file)
     }
 
     // Signed Integers
@@ -81,6 +113,41 @@ impl LazyData {
     new_array!((new_f32_array) f32 = F32);
     new_array!((new_f64_array) f64 = F64);
 
+    /// Creates a new `LazyData` file with an array of strings (each prefixed with it's length as a big-endian `u64`)
+    pub fn new_string_array(mut file: FileWrapper, value: &[impl AsRef<str>]) -> Result<(), LDBError> {
+        file.write(&[LazyType::Array.into(), LazyType::String.into()])?;
+        for i in value {
+            let bytes = i.as_ref().as_bytes();
+            file.write(&(bytes.len() as u64).to_be_bytes())?;
+            file.write(bytes)?;
+        }
+        finish(file)
+    }
+
+    /// Creates a new `LazyData` file with an array of binaries (each prefixed with it's length as a big-endian `u64`)
+    pub fn new_binary_array(mut file: FileWrapper, value: &[impl AsRef<[u8]>]) -> Result<(), LDBError> {
+        file.write(&[LazyType::Array.into(), LazyType::Binary.into()])?;
+        for i in value {
+            let bytes = i.as_ref();
+            file.write(&(bytes.len() as u64).to_be_bytes())?;
+            file.write(bytes)?;
+        }
+        finish(file)
+    }
+
+    /// Creates a new `LazyData` file with an array of booleans packed into bits (most significant bit first)
+    /// 
+    /// The element type is `LazyType::True` and is followed by the amount of unused bits in the last byte
+    pub fn new_bool_array(mut file: FileWrapper, value: &[bool]) -> Result<(), LDBError> {
+        let mut bytes = vec![0u8; value.len().div_ceil(8)];
+        for (i, x) in value.iter().enumerate() {
+            if *x { bytes[i / 8] |= 0x80 >> (i % 8) };
+        }
+        file.write(&[LazyType::Array.into(), LazyType::True.into(), ((8 - value.len() % 8) % 8) as u8])?;
+        file.write(&bytes)?;
+        finish(file)
+    }
+
     /* Floating point numbers */
 
     /// Creates a new `LazyData` file with an `f32` value and type
@@ -88,7 +155,7 @@ impl LazyData {
         let bytes = value.to_be_bytes();
         file.write(&[LazyType::F32.into()])?;
         file.write(&bytes)?;
-        Ok(())
+        finish(file)
     }
 
     /// Creates a new `LazyData` file with an `f64` value and type
@@ -96,27 +163,189 @@ impl LazyData {
         let bytes = value.to_be_bytes();
         file.write(&[LazyType::F64.into()])?;
         file.write(&bytes)?;
-        Ok(())
+        finish(file)
     }
 
     /// Creates a new `LazyData` file with a `binary` value and type
     pub fn new_binary(mut file: FileWrapper, value: &[u8]) -> Result<(), LDBError> {
         file.write(&[LazyType::Binary.into()])?;
-        file.write(value)
+        file.write(value)?;
+        finish(file)
     }
 
     /// Creates a new `LazyData` file with a `bool` value and type
     pub fn new_bool(mut file: FileWrapper, value: bool) -> Result<(), LDBError> {
         if value {
-            file.write(&[LazyType::True.into()])
+            file.write(&[LazyType::True.into()])?;
         } else {
-            file.write(&[LazyType::False.into()])
+            file.write(&[LazyType::False.into()])?;
+        }
+        finish(file)
+    }
+
+    /// Creates a new `LazyData` file with a `LazyTimestamp` value and type
+    pub fn new_timestamp(mut file: FileWrapper, value: LazyTimestamp) -> Result<(), LDBError> {
+        file.write(&[LazyType::Timestamp.into()])?;
+        file.write(&value.secs.to_be_bytes())?;
+        file.write(&value.nanos.to_be_bytes())?;
+        finish(file)
+    }
+
+    /// Creates a new `LazyData` file with a `Duration` value and type
+    pub fn new_duration(mut file: FileWrapper, value: Duration) -> Result<(), LDBError> {
+        file.write(&[LazyType::Duration.into()])?;
+        file.write(&value.as_secs().to_be_bytes())?;
+        file.write(&value.subsec_nanos().to_be_bytes())?;
+        finish(file)
+    }
+
+    /// Creates a new `LazyData` file with a `LazyUuid` value and type
+    pub fn new_uuid(mut file: FileWrapper, value: LazyUuid) -> Result<(), LDBError> {
+        file.write(&[LazyType::Uuid.into()])?;
+        file.write(&value.0)?;
+        finish(file)
+    }
+
+    /// Creates a new `LazyData` file with a `char` value (as it's unicode code point) and type
+    pub fn new_char(mut file: FileWrapper, value: char) -> Result<(), LDBError> {
+        file.write(&[LazyType::Char.into()])?;
+        file.write(&(value as u32).to_be_bytes())?;
+        finish(file)
+    }
+
+    /// Creates a new `LazyData` file with a `LazyDecimal` value and type
+    ///
+    /// Returns `LDBError::InvalidValue` if it's scale is more than `LazyDecimal::MAX_SCALE`
+    pub fn new_decimal(mut file: FileWrapper, value: LazyDecimal) -> Result<(), LDBError> {
+        value.check()?;
+        file.write(&[LazyType::Decimal.into()])?;
+        file.write(&value.mantissa.to_be_bytes())?;
+        file.write(&value.scale.to_be_bytes())?;
+        finish(file)
+    }
+
+    /// Creates a new `LazyData` file with an optional value
+    /// 
+    /// It's stored as a flag of whether the value is present followed by the value (with it's own `LazyType`)
+    pub fn new_option<T: ToLazy + ?Sized>(mut file: FileWrapper, value: Option<&T>) -> Result<(), LDBError> {
+        file.write(&[LazyType::Option.into(), value.is_some() as u8])?;
+        match value {
+            Some(x) => x.to_lazy(file),
+            None => finish(file),
         }
     }
 
-    /// Creates a new `LazyData` file with a link (it's like a reference) value and type
-    pub fn new_link(mut file: FileWrapper, data: impl AsRef<Path>) -> Result<(), LDBError> {
+    /// Creates a new `LazyData` file with a variant of an enum (a tagged union)
+    /// 
+    /// It's stored as the variant's index (`u32`), name (length-prefixed `u32`) and then it's payload (with it's own `LazyType`);
+    /// unit variants can use `&()` as their payload
+    pub fn new_enum<T: ToLazy + ?Sized>(mut file: FileWrapper, index: u32, name: &str, payload: &T) -> Result<(), LDBError> {
+        file.write(&[LazyType::Enum.into()])?;
+        file.write(&index.to_be_bytes())?;
+        file.write(&(name.len() as u32).to_be_bytes())?;
+        file.write(name.as_bytes())?;
+        payload.to_lazy(file)
+    }
+
+    /// Creates a new `LazyData` file with an inline map of keys to values (see `LazyMap`)
+    pub fn new_map(mut file: FileWrapper, value: &LazyMap) -> Result<(), LDBError> {
+        file.write(&[LazyType::Map.into()])?;
+        value.write_entries(&mut file)?;
+        finish(file)
+    }
+
+    /// Creates a new `LazyData` file with a link (it's like a reference) to data or a container
+    ///
+    /// Paths starting with `/` are from the root of the database, otherwise they're relative to the container the link is in (`..` is it's parent)
+    pub fn new_link(mut file: FileWrapper, target: impl AsRef<Path>) -> Result<(), LDBError> {
+        let target = target.as_ref();
+        let Some(string) = target.to_str() else { return Err(LDBError::InvalidUTF8String(target.as_os_str().as_encoded_bytes().into())) };
         file.write(&[LazyType::Link.into()])?;
-        file.write(data.as_ref().as_os_str().as_bytes())
+        file.write(string.as_bytes())?;
+        finish(file)
+    }
+}
+to_lazy! {
+    (new_void) ();
+    (new_bool) bool;
+    (new_u8) u8;
+    (new_u16) u16;
+    (new_u32) u32;
+    (new_u64) u64;
+    (new_u128) u128;
+    (new_i8) i8;
+    (new_i16) i16;
+    (new_i32) i32;
+    (new_i64) i64;
+    (new_i128) i128;
+    (new_f32) f32;
+    (new_f64) f64;
+    (new_timestamp) LazyTimestamp;
+    (new_duration) Duration;
+    (new_uuid) LazyUuid;
+    (new_char) char;
+    (new_decimal) LazyDecimal;
+}
+
+impl ToLazy for str {
+    #[inline]
+    fn to_lazy(&self, file: FileWrapper) -> Result<(), LDBError> {
+        LazyData::new_string(file, self)
+    }
+}
+
+impl ToLazy for String {
+    #[inline]
+    fn to_lazy(&self, file: FileWrapper) -> Result<(), LDBError> {
+        LazyData::new_string(file, self)
+    }
+}
+
+impl<T: LazyArrayItem> ToLazy for [T] {
+    #[inline]
+    fn to_lazy(&self, file: FileWrapper) -> Result<(), LDBError> {
+        T::new_array(file, self)
+    }
+}
+
+impl<T: LazyArrayItem, const N: usize> ToLazy for [T; N] {
+    #[inline]
+    fn to_lazy(&self, file: FileWrapper) -> Result<(), LDBError> {
+        T::new_array(file, self)
     }
-}
\ No newline at end of file
+}
+
+impl<T: LazyArrayItem> ToLazy for Vec<T> {
+    #[inline]
+    fn to_lazy(&self, file: FileWrapper) -> Result<(), LDBError> {
+        T::new_array(file, self)
+    }
+}
+
+impl<T: LazyArrayItem> ToLazy for Box<[T]> {
+    #[inline]
+    fn to_lazy(&self, file: FileWrapper) -> Result<(), LDBError> {
+        T::new_array(file, self)
+    }
+}
+
+impl ToLazy for LazyMap {
+    #[inline]
+    fn to_lazy(&self, file: FileWrapper) -> Result<(), LDBError> {
+        LazyData::new_map(file, self)
+    }
+}
+
+impl<T: ToLazy> ToLazy for Option<T> {
+    #[inline]
+    fn to_lazy(&self, file: FileWrapper) -> Result<(), LDBError> {
+        LazyData::new_option(file, self.as_ref())
+    }
+}
+
+impl<T: ToLazy + ?Sized> ToLazy for &T {
+    #[inline]
+    fn to_lazy(&self, file: FileWrapper) -> Result<(), LDBError> {
+        (**self).to_lazy(file)
+    }
+}
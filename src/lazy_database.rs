@@ -1,33 +1,41 @@
 use crate::*;
+use crate::lazy_wal::{self, Wal};
+use crate::lazy_lock::{self, DbLock};
+use crate::lazy_link::Links;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::io::Write;
 use std::fs;
 
 /// Used for reading from a `LazyDB` with less boiler-plate
+/// 
+/// Links (`LazyType::Link`) are followed transparently, both to containers along the path and to the data read
 #[macro_export]
 macro_rules! search_database {
     (($ldb:expr) /$($($con:ident)?$(($can:expr))?)/ *) => {(|| {
         let database = &$ldb;
         let container = database.as_container()?;
         $(
-            $(let container = container.child_container(stringify!($con))?;)?
-            $(let container = container.child_container($can)?;)?
+            $(let container = database.follow_container(&container, stringify!($con))?;)?
+            $(let container = database.follow_container(&container, $can)?;)?
         )*
         let result: Result<LazyContainer, LDBError> = Ok(container);
         result
     })()};
 
     (($ldb:expr) /$($($con:ident)?$(($can:expr))?)/ *::$($item:ident)?$(($obj:expr))?) => {(|| {
-        let container = search_database!(($ldb) /$($($con)?$(($can))?)/ *)?;
-        $(let result: Result<LazyData, LDBError> = container.read_data(stringify!($item));)?
-        $(let result: Result<LazyData, LDBError> = container.read_data($obj);)?
+        let database = &$ldb;
+        let container = search_database!((database) /$($($con)?$(($can))?)/ *)?;
+        $(let result: Result<LazyData, LDBError> = container.read_data(stringify!($item)).and_then(|x| database.follow_link(x));)?
+        $(let result: Result<LazyData, LDBError> = container.read_data($obj).and_then(|x| database.follow_link(x));)?
         result
     })()};
 
     (($ldb:expr) $($item:ident)?$(($obj:expr))?) => {(|| {
         let database = &$ldb;
         let container = database.as_container()?;
-        $(let result: Result<LazyData, LDBError> = container.read_data(stringify!($item));)?
-        $(let result: Result<LazyData, LDBError> = container.read_data($obj);)?
+        $(let result: Result<LazyData, LDBError> = container.read_data(stringify!($item)).and_then(|x| database.follow_link(x));)?
+        $(let result: Result<LazyData, LDBError> = container.read_data($obj).and_then(|x| database.follow_link(x));)?
         result
     })()};
 }
@@ -43,18 +51,41 @@ macro_rules! write_database {
         Result::<(), LDBError>::Ok(())
     })()};
 
+    (($ldb:expr) $($item:ident)?$(($obj:expr))? = $value:expr) => {(|| {
+        let database = &$ldb;
+        let container = database.as_container()?;
+        $(container.write(stringify!($item), $value)?;)?
+        $(container.write($obj, $value)?;)?
+        Result::<(), LDBError>::Ok(())
+    })()};
+
     (($ldb:expr) /$($($con:ident)?$(($can:expr))?)/ *::$($item:ident)?$(($obj:expr))? = $func:ident($value:expr)) => {(|| {
         let mut container = search_database!(($ldb) /$($($con)?$(($can))?)/ *)?;
 
         $(LazyData::$func(container.data_writer(stringify!($item))?, $value)?;)?
         $(LazyData::$func(container.data_writer($obj)?, $value)?;)?
         Result::<(), LDBError>::Ok(())
+    })()};
+
+    (($ldb:expr) /$($($con:ident)?$(($can:expr))?)/ *::$($item:ident)?$(($obj:expr))? = $value:expr) => {(|| {
+        let container = search_database!(($ldb) /$($($con)?$(($can))?)/ *)?;
+
+        $(container.write(stringify!($item), $value)?;)?
+        $(container.write($obj, $value)?;)?
+        Result::<(), LDBError>::Ok(())
     })()}
 }
 
 pub struct LazyDB {
     path: PathBuf,
     compressed: bool,
+    wal: Option<Arc<Wal>>,
+    read_only: bool,
+    locks: Vec<Arc<DbLock>>,
+    /// A `StorageBackend` other than the filesystem
+    backend: Option<Arc<dyn StorageBackend>>,
+    /// How the database's links resolve
+    pub(crate) links: Arc<Links>,
 }
 
 impl LazyDB {
@@ -62,6 +93,8 @@ impl LazyDB {
     /// 
     /// It will create the path if it doesn't already exist and initialise a metadata file with the current version of `lazy-db` if one doesn't exist already.
     /// 
+    /// The database is locked exclusively; if another process holds it's lock, it will return `LDBError::DatabaseLocked`.
+    /// 
     /// **WARNING:** if you initialise the database this way, you cannot compile it in future without errors being thrown!
     /// If you want to compile it, then use `LazyDB::init_db` instead.
     pub fn init(path: impl AsRef<Path>) -> Result<Self, LDBError> {
@@ -69,33 +102,32 @@ impl LazyDB {
 
         // Check if path exists or not if init it
         if !path.is_dir() { unwrap_result!((fs::create_dir_all(path)) err => LDBError::IOError(err)) };
-        
-        { // Check if `.meta` file exists if not 
-            let meta = path.join(".meta");
-            if !meta.is_file() {
-                // Write version
-                LazyData::new_binary(
-                    FileWrapper::new_writer(
-                        unwrap_result!((fs::File::create(meta)) err => LDBError::IOError(err))
-                    ), &[VERSION.major, VERSION.minor, VERSION.build],
-                )?;
-            }
-        };
+        let lock = DbLock::acquire(path.join(lazy_lock::LOCK_FILE), LockMode::Exclusive, LockWait::Try)?;
+        let version = Self::init_meta(&FsBackend::new(), path)?;
 
         // Construct Self
-        Ok(Self {
+        Self {
             path: path.to_path_buf(),
             compressed: false,
-        })
+            wal: Self::open_wal(path)?,
+            read_only: false,
+            locks: vec![lock],
+            backend: None,
+            links: Arc::new(Links::new(path, &VERSION)),
+        }.upgrade(&version)
     }
 
-    /// Initialise a new compiled `LazyDB` (compressed tarball) at the specified path.
+    /// Initialise a new compiled `LazyDB` (compressed archive) at the specified path.
     ///
     /// It will create the path if it doesn't already exist and initialise a metadata file with the current version of `lazy-db` if one doesn't exist already.
     pub fn init_db(path: impl AsRef<Path>) -> Result<Self, LDBError> {
-        let dir_path = path.as_ref().with_extension("modb");
-        let mut this = Self::init(dir_path)?;
+        let path = path.as_ref();
+        let dir = path.with_extension("modb");
+        if !dir.is_dir() { unwrap_result!((fs::create_dir_all(&dir)) err => LDBError::IOError(err)) }; // so the lock's path can be canonicalized
+        let lock = DbLock::acquire(path.with_extension("lock"), LockMode::Exclusive, LockWait::Try)?;
+        let mut this = Self::init(dir)?;
         this.compressed = true;
+        this.locks.push(lock);
         Ok(this)
     }
 
@@ -103,48 +135,278 @@ impl LazyDB {
     /// 
     /// Loads LazyDB as `read-write` allowing for modification of the data within it.
     /// 
+    /// If a transaction was interrupted while committing, it will be finished; otherwise uncommitted transactions are discarded.
+    /// Databases of versions that resolved every link from the root have their nested links rewritten to start with `/` (and indexed).
+    /// 
+    /// The database is locked exclusively; if another process holds it's lock, it will return `LDBError::DatabaseLocked`.
+    /// 
     /// If the LazyDB is invalid, it will return an error.
+    #[inline]
     pub fn load_dir(path: impl AsRef<Path>) -> Result<Self, LDBError> {
+        Self::load_dir_with(path, LockMode::Exclusive, LockWait::Try)
+    }
+
+    /// Loads a pre-existing LazyDB directory at a specified path with a specific `LockMode`,
+    /// waiting for other processes to release the lock as specified by the `LockWait`.
+    /// 
+    /// Use `LockMode::Shared` to let many processes read the database at once; the database is then read-only (see `LazyDB::open_read_only`).
+    pub fn load_dir_with(path: impl AsRef<Path>, mode: LockMode, wait: LockWait) -> Result<Self, LDBError> {
         let path = path.as_ref();
 
         // Checks if path exists
         if !path.is_dir() { return Err(LDBError::DirNotFound(path.to_path_buf())) };
+        let lock = DbLock::acquire(path.join(lazy_lock::LOCK_FILE), mode, wait)?;
+
+        let version = Self::check_meta(&FsBackend::new(), path)?;
+
+        // Readers leave the database untouched
+        let read_only = mode == LockMode::Shared;
+        if read_only {
+            return Ok(Self {
+                path: path.to_path_buf(),
+                compressed: false,
+                wal: None,
+                read_only,
+                locks: vec![lock],
+                backend: None,
+                links: Arc::new(Links::new(path, &version)),
+            });
+        }
+
+        // Finishes or discards any interrupted mutations and transactions
+        let wal = Self::open_wal(path)?;
+        lazy_transaction::recover(&FsBackend::new(), path)?;
+        if Arc::strong_count(&lock) == 1 { // no other handle of this process is writing to it
+            unwrap_result!((remove_leftovers(path)) err => LDBError::IOError(err));
+        }
+
+        // Constructs Self
+        Self {
+            path: path.to_path_buf(),
+            compressed: false,
+            wal,
+            read_only,
+            locks: vec![lock],
+            backend: None,
+            links: Arc::new(Links::new(path, &VERSION)),
+        }.upgrade(&version)
+    }
+
+    /// Opens a pre-existing LazyDB directory or file (compressed archive) at a specified path as `read-only`
+    /// 
+    /// Every write through the database (or it's `LazyContainer`s) returns `LDBError::ReadOnly`,
+    /// and the database is only locked with a shared lock so that many readers can open it at once.
+    /// 
+    /// Compiled databases are read directly from the file (see `ArchiveBackend`), only decompressing the `LazyData` that is read;
+    /// they're never recompiled and no `.modb` directory is left behind.
+    /// (Databases compiled by older versions of `lazy-db` are decompiled into a temporary directory that is removed on drop)
+    /// 
+    /// **Note:** interrupted mutations and transactions aren't recovered; load the database as `read-write` to recover them.
+    #[inline]
+    pub fn open_read_only(path: impl AsRef<Path>) -> Result<Self, LDBError> {
+        Self::open_read_only_with(path, LockWait::Try)
+    }
+
+    /// Opens a pre-existing LazyDB directory or file as `read-only` (see `LazyDB::open_read_only`),
+    /// waiting for writers to release the lock as specified by the `LockWait`.
+    pub fn open_read_only_with(path: impl AsRef<Path>, wait: LockWait) -> Result<Self, LDBError> {
+        let path = path.as_ref();
+        if path.is_dir() { return Self::load_dir_with(path, LockMode::Shared, wait) };
+        let lock = DbLock::acquire(path.with_extension("lock"), LockMode::Shared, wait)?;
+
+        // Reads the compiled database directly
+        if ArchiveBackend::is_indexed(path) {
+            let backend: Arc<dyn StorageBackend> = Arc::new(ArchiveBackend::open(path)?);
+            let version = Self::check_meta(&*backend, Path::new("/"))?;
+            return Ok(Self {
+                path: PathBuf::from("/"),
+                compressed: false,
+                wal: None,
+                read_only: true,
+                locks: vec![lock],
+                backend: Some(backend),
+                links: Arc::new(Links::new("/", &version)),
+            });
+        }
 
-        // Checks if `.meta` file exists or not
+        // Decompiles older databases into a temporary directory
+        let name = path.file_name().unwrap_or_default();
+        let tmp = hidden_sibling(&std::env::temp_dir().join(name), "ro");
+        let ldb = Self::decompile(path, &tmp)
+            .and_then(|_| Self::load_dir_with(&tmp, LockMode::Shared, wait));
+        let mut ldb = match ldb {
+            Ok(x) => x,
+            Err(e) => { let _ = fs::remove_dir_all(&tmp); return Err(e) },
+        };
+        ldb.compressed = true;
+        ldb.locks.push(lock);
+
+        Ok(ldb)
+    }
+
+    /// Initialises a new LazyDB at a specified path of a `StorageBackend` (see `LazyDB::init`)
+    /// 
+    /// Databases on other backends aren't locked and don't support write-ahead logs.
+    pub fn init_in(backend: Arc<dyn StorageBackend>, path: impl AsRef<Path>) -> Result<Self, LDBError> {
+        let path = path.as_ref();
+        if backend.kind(path) != Some(EntryKind::Container) {
+            unwrap_result!((backend.create_dir(path)) err => LDBError::IOError(err));
+        }
+        let version = Self::init_meta(&*backend, path)?;
+
+        // Construct Self
+        Self {
+            path: path.to_path_buf(),
+            compressed: false,
+            wal: None,
+            read_only: false,
+            locks: Vec::new(),
+            backend: Some(backend),
+            links: Arc::new(Links::new(path, &VERSION)),
+        }.upgrade(&version)
+    }
+
+    /// Loads a pre-existing LazyDB at a specified path of a `StorageBackend` (see `LazyDB::load_dir`)
+    pub fn load_in(backend: Arc<dyn StorageBackend>, path: impl AsRef<Path>) -> Result<Self, LDBError> {
+        let path = path.as_ref();
+
+        // Checks if path exists
+        if backend.kind(path) != Some(EntryKind::Container) { return Err(LDBError::DirNotFound(path.to_path_buf())) };
+        let version = Self::check_meta(&*backend, path)?;
+        lazy_transaction::recover(&*backend, path)?;
+
+        // Constructs Self
+        Self {
+            path: path.to_path_buf(),
+            compressed: false,
+            wal: None,
+            read_only: false,
+            locks: Vec::new(),
+            backend: Some(backend),
+            links: Arc::new(Links::new(path, &VERSION)),
+        }.upgrade(&version)
+    }
+
+    /// Initialises a new, empty LazyDB that only lives in memory (see `MemoryBackend`)
+    /// 
+    /// It supports the full `LazyContainer` / `LazyData` API and can be compiled with `LazyDB::compile` or `LazyDB::compile_to`,
+    /// but everything is lost once it's dropped.
+    #[inline]
+    pub fn in_memory() -> Result<Self, LDBError> {
+        Self::init_in(Arc::new(MemoryBackend::new()), "/")
+    }
+
+    /// Writes a metadata file with the current version of `lazy-db` if one doesn't exist already, returning the database's version
+    fn init_meta(backend: &dyn StorageBackend, path: &Path) -> Result<version::Version, LDBError> {
         let meta = path.join(".meta");
-        if !meta.is_file() { return Err(LDBError::FileNotFound(meta)) };
+        if backend.kind(&meta) == Some(EntryKind::Data) { return Self::check_meta(backend, path) };
+        Self::write_meta(backend, path)?;
+        Ok(VERSION)
+    }
+
+    /// Writes a metadata file with the current version of `lazy-db`
+    fn write_meta(backend: &dyn StorageBackend, path: &Path) -> Result<(), LDBError> {
+        LazyData::new_binary(
+            unwrap_result!((backend.open_writer(&path.join(".meta"))) err => LDBError::IOError(err)),
+            &[VERSION.major, VERSION.minor, VERSION.build],
+        )
+    }
 
-        // Checks validity of version
-        let read_version = LazyData::load(&meta)?.collect_binary()?;
+    /// Migrates a writable database of an older version of `lazy-db` whose links would now resolve differently (see `LazyDB::migrate_links`)
+    /// and indexes it's links (see `LazyDB::reindex_links`), updating it to the current version
+    fn upgrade(self, version: &version::Version) -> Result<Self, LDBError> {
+        if lazy_link::relative_links(version) { return Ok(self) };
+        self.migrate_links()?;
+        self.reindex_links()?;
+        Self::write_meta(&*self.backend(), &self.path)?;
+        Ok(self)
+    }
+
+    /// Checks that the metadata file exists and that it's version is compatible, returning it
+    fn check_meta(backend: &dyn StorageBackend, path: &Path) -> Result<version::Version, LDBError> {
+        let meta = path.join(".meta");
+        let read_version = LazyData::load_from(backend, &meta)?.collect_binary()?;
         if read_version.len() != 3 { return Err(LDBError::InvalidMetaVersion(meta)) };
         let read_version = version::Version::new(read_version[0], read_version[1], read_version[2]);
         if !VERSION.is_compatible(&read_version) { return Err(LDBError::IncompatibleVersion(read_version)) };
+        Ok(read_version)
+    }
 
-        // Constructs Self
-        Ok(Self {
-            path: path.to_path_buf(),
-            compressed: false,
-        })
+    /// Recovers and opens the write-ahead log of the database if it has one
+    fn open_wal(path: &Path) -> Result<Option<Arc<Wal>>, LDBError> {
+        if !Wal::exists(path) { return Ok(None) };
+        lazy_wal::recover(path)?;
+        Ok(Some(unwrap_result!((Wal::open(path)) err => LDBError::IOError(err))))
     }
 
-    /// Loads a pre-existing LazyDB file (compressed tarball) at a specified path
+    /// Enables the write-ahead log of the database
+    /// 
+    /// Every mutation made through the database's `LazyContainer`s is logged before it's applied,
+    /// so that `LazyDB::load_dir` / `LazyDB::load_db` can finish or discard incomplete mutations after a crash.
+    /// Once enabled, it stays enabled whenever the database is loaded.
+    /// 
+    /// **Note:** `LazyContainer`s gotten from the database before enabling it won't be logged
+    pub fn enable_wal(&mut self) -> Result<(), LDBError> {
+        if self.read_only { return Err(LDBError::ReadOnly(self.path.clone())) };
+        if self.backend.is_some() {
+            return Err(LDBError::IOError(std::io::Error::new(std::io::ErrorKind::Unsupported, "write-ahead logs are only supported on the filesystem")));
+        }
+        if self.wal.is_none() {
+            self.wal = Some(unwrap_result!((Wal::open(&self.path)) err => LDBError::IOError(err)));
+        }; Ok(())
+    }
+
+    /// Disables and removes the write-ahead log of the database
+    pub fn disable_wal(&mut self) -> Result<(), LDBError> {
+        if self.read_only { return Err(LDBError::ReadOnly(self.path.clone())) };
+        if self.wal.is_none() { return Ok(()) };
+        self.wal = None;
+        let path = self.path.join(lazy_wal::WAL_FILE);
+        if path.is_file() { unwrap_result!((fs::remove_file(path)) err => LDBError::IOError(err)) };
+        Ok(())
+    }
+
+    /// Checks if the write-ahead log of the database is enabled
+    #[inline]
+    pub fn wal_enabled(&self) -> bool {
+        self.wal.is_some()
+    }
+
+    /// Loads a pre-existing LazyDB file (compressed archive) at a specified path
     /// 
     /// Loads LazyDB as `read-write` allowing for modification of the data within it.
     /// 
     /// If a directory version of the LazyDatabase exists, it will load the directory version instead of decompiling.
     /// 
+    /// The database (and the lock file next to it) is locked exclusively; if another process holds it's lock, it will return `LDBError::DatabaseLocked`.
+    /// 
     /// If the LazyDB is invalid, it will return an error.
+    #[inline]
     pub fn load_db(path: impl AsRef<Path>) -> Result<Self, LDBError> {
+        Self::load_db_with(path, LockWait::Try)
+    }
+
+    /// Loads a pre-existing LazyDB file (compressed archive) at a specified path,
+    /// waiting for other processes to release the lock as specified by the `LockWait`.
+    /// 
+    /// Compiled databases are always locked exclusively, as they're decompiled into (and recompiled from) a directory next to them.
+    pub fn load_db_with(path: impl AsRef<Path>, wait: LockWait) -> Result<Self, LDBError> {
         let path = path.as_ref();
         let mod_path = path.with_extension("modb");
+        let lock = DbLock::acquire(path.with_extension("lock"), LockMode::Exclusive, wait)?;
 
         // Checks if other loaded version exists
-        if mod_path.is_dir() { return Self::load_dir(mod_path) }
+        if mod_path.is_dir() {
+            let mut ldb = Self::load_dir_with(mod_path, LockMode::Exclusive, wait)?;
+            ldb.locks.push(lock);
+            return Ok(ldb);
+        }
 
         // Decompiles database
         Self::decompile(path, &mod_path)?;
-        let mut ldb = Self::load_dir(mod_path)?;
+        let mut ldb = Self::load_dir_with(mod_path, LockMode::Exclusive, wait)?;
         ldb.compressed = true;
+        ldb.locks.push(lock);
 
         Ok(ldb)
     }
@@ -152,7 +414,17 @@ impl LazyDB {
     /// Gets the 'root' container of the `LazyDB`
     #[inline]
     pub fn as_container(&self) -> Result<LazyContainer, LDBError> {
-        LazyContainer::load(&self.path)
+        Ok(LazyContainer::load_in(self.backend(), &self.path)?
+            .with_read_only(self.read_only)
+            .with_links(self.links.clone()))
+    }
+
+    /// Gets the `StorageBackend` that the database is stored in
+    pub fn backend(&self) -> Arc<dyn StorageBackend> {
+        match &self.backend {
+            Some(x) => x.clone(),
+            None => Arc::new(FsBackend::logged(self.wal.clone())),
+        }
     }
 
     #[inline]
@@ -160,22 +432,27 @@ impl LazyDB {
         &self.path
     }
 
-    /// Compiles a modifiable `LazyDatabase` directory into a compressed tarball (doesn't delete the modifable directory).
-    pub fn compile(&self, out_path: impl AsRef<Path>) -> Result<(), std::io::Error> {
-        use lazy_archive::*; // imports
-        let tar = self.path.with_extension("tmp.tar");
-
-        // Build and compress tarball
-        build_tar(&self.path, &tar)?; // build tar
-        compress_file(&tar, &out_path)?;
+    /// Checks if the database refuses all writes (see `LazyDB::open_read_only`)
+    #[inline]
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
 
-        // Clean-up
-        fs::remove_file(tar)?;
+    /// Compiles a modifiable `LazyDatabase` directory into a compressed, indexed archive (doesn't delete the modifable directory).
+    pub fn compile(&self, out_path: impl AsRef<Path>) -> Result<(), std::io::Error> {
+        let mut out = std::io::BufWriter::new(fs::File::create(out_path)?);
+        self.compile_to(&mut out)?;
+        out.flush()
+    }
 
-        Ok(())
+    /// Compiles the `LazyDatabase` into a compressed, indexed archive written to any writer (like a `Vec<u8>` byte buffer)
+    /// 
+    /// The result can be written to a file and loaded with `LazyDB::load_db`
+    pub fn compile_to(&self, out: impl Write) -> Result<(), std::io::Error> {
+        lazy_archive::build_indexed(&*self.backend(), &self.path, out)
     }
 
-    /// Decompiles a compressed tarball `LazyDatabase` into a modifiable directory (doesn't remove the compressed tarball)
+    /// Decompiles a compiled `LazyDatabase` into a modifiable directory (doesn't remove the compiled file)
     pub fn decompile(path: impl AsRef<Path>, out_path: impl AsRef<Path>) -> Result<(), LDBError> {
         use lazy_archive::*; // imports
         let path = path.as_ref();
@@ -183,13 +460,10 @@ impl LazyDB {
         // Checks if the path exists
         if !path.is_file() { return Err(LDBError::FileNotFound(path.to_path_buf())) };
 
-        // Decompress and unpack
-        let tar = path.with_extension("tmp.tar");
-        unwrap_result!((decompress_file(path, &tar)) err => LDBError::IOError(err));
-        unwrap_result!((unpack_tar(&tar, out_path)) err => LDBError::IOError(err));
-
-        // Clean-up
-        unwrap_result!((fs::remove_file(tar)) err => LDBError::IOError(err));
+        // Decompress and unpack (supporting the compressed tarballs of older versions)
+        let result = if ArchiveBackend::is_indexed(path) { unpack_indexed(path, out_path) }
+            else { unpack_compressed(path, out_path) };
+        unwrap_result!((result) err => LDBError::IOError(err));
         
         Ok(())
     }
@@ -198,6 +472,8 @@ impl LazyDB {
 impl Drop for LazyDB {
     fn drop(&mut self) {
         if !self.compressed { return }; // If not compressed do nothing
+        if self.read_only { let _ = fs::remove_dir_all(&self.path); return }; // Never recompile a read-only copy
+        if let Some(wal) = &self.wal { let _ = wal.checkpoint(); }; // Don't compile finished mutations into the log
         let ok = self.compile(self.path.with_extension("ldb")).is_ok();
         if !ok { return }; // Don't delete if not ok
         let _ = fs::remove_dir_all(&self.path);
@@ -0,0 +1,366 @@
+use crate::*;
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::io::{self, Write};
+use std::fmt;
+
+/// The most links followed when resolving a chain of links (see `LazyDB::resolve_link`)
+pub const MAX_LINK_DEPTH: usize = 32;
+/// The name of the reverse-reference index of a database's links (next to `.meta`)
+pub(crate) const LINK_INDEX: &str = ".links";
+/// The first version (major, minor) of `lazy-db` that resolves links relative to their container; older versions resolve them from the root
+const RELATIVE_LINKS: (u8, u8) = (1, 4);
+
+/// Held while the index of links is changed and the links are written (see `Links::record`),
+/// and while `Links::remove` finds the links to what's being removed and removes it
+static LINK_LOCK: Mutex<()> = Mutex::new(());
+
+/// Checks if a database of a version resolves links relative to their container
+#[inline]
+pub(crate) fn relative_links(version: &version::Version) -> bool {
+    (version.major, version.minor) >= RELATIVE_LINKS
+}
+
+/// What `LazyContainer::remove_with` does when links still point to what's being removed
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum RemovePolicy {
+    /// Removes it anyway, leaving the links dangling (like `LazyContainer::remove`)
+    #[default]
+    Ignore,
+    /// Refuses to remove it, returning `LDBError::StillLinked`
+    Restrict,
+    /// Also removes the links (and any links to them)
+    Cascade,
+}
+
+/// A link that doesn't resolve, found by `LazyDB::check_links`
+pub struct BrokenLink {
+    /// Where the link is within the database
+    pub location: WalkEntry,
+    /// The path the link points to
+    pub target: PathBuf,
+    /// Why it doesn't resolve
+    pub error: LDBError,
+}
+
+/// Formats the broken link as `/a/b::key -> target: error`
+impl fmt::Display for BrokenLink {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} -> {}: {}", self.location, self.target.to_string_lossy(), self.error)
+    }
+}
+
+/// What a `LazyType::Link` points to once resolved
+pub enum LinkTarget {
+    Data(LazyData),
+    Container(LazyContainer),
+}
+
+impl LinkTarget {
+    /// Returns `LDBError::IncorrectType` if the link points to a container
+    pub fn into_data(self) -> Result<LazyData, LDBError> {
+        match self {
+            Self::Data(x) => Ok(x),
+            Self::Container(_) => Err(LDBError::IncorrectType(LazyType::Link, String::from("LazyData"))),
+        }
+    }
+
+    /// Returns `LDBError::IncorrectType` if the link points to data
+    pub fn into_container(self) -> Result<LazyContainer, LDBError> {
+        match self {
+            Self::Container(x) => Ok(x),
+            Self::Data(_) => Err(LDBError::IncorrectType(LazyType::Link, String::from("LazyContainer"))),
+        }
+    }
+}
+
+impl LazyDB {
+    /// Resolves a `LazyType::Link` (following a chain of links) to the data or container it points to
+    ///
+    /// Returns `LDBError::LinkCycle` if the chain loops back on itself, `LDBError::LinkDepthExceeded` if it's longer than `MAX_LINK_DEPTH`
+    /// and `LDBError::FileNotFound` if the link is dangling
+    pub fn resolve_link(&self, link: LazyData) -> Result<LinkTarget, LDBError> {
+        let root = self.as_container()?;
+        let mut visited = HashSet::new();
+        let mut link = link;
+
+        loop {
+            if !visited.insert(link.path.clone()) { return Err(LDBError::LinkCycle(link.path)) };
+            if visited.len() > MAX_LINK_DEPTH { return Err(LDBError::LinkDepthExceeded(link.path, MAX_LINK_DEPTH)) };
+
+            let link_path = link.path.clone();
+            let key = self.links.target(&link_path, &link.collect_link_path()?)?;
+            let path = self.path().join(&key);
+            match root.backend().kind(&path) {
+                Some(EntryKind::Container) => return Ok(LinkTarget::Container(root.read_container(&key)?)),
+                Some(EntryKind::Data) => {
+                    let data = root.read_data(&key)?;
+                    if data.lazy_type != LazyType::Link { return Ok(LinkTarget::Data(data)) };
+                    link = data;
+                },
+                None => return Err(LDBError::FileNotFound(path)),
+            }
+        }
+    }
+
+    /// Follows `LazyData` to what it points to if it's a link, otherwise returns it as is (used by `search_database!`)
+    #[inline]
+    pub fn follow_link(&self, data: LazyData) -> Result<LazyData, LDBError> {
+        if data.lazy_type != LazyType::Link { return Ok(data) };
+        data.collect_link(self)
+    }
+
+    /// Gets a nested container like `LazyContainer::child_container`, but following the key if it's a link to a container (used by `search_database!`)
+    pub fn follow_container(&self, container: &LazyContainer, key: impl AsRef<Path>) -> Result<LazyContainer, LDBError> {
+        let key = key.as_ref();
+        if container.contains_data(key) {
+            let data = container.read_data(key)?;
+            if data.lazy_type == LazyType::Link { return self.resolve_link(data)?.into_container() };
+        }
+        container.child_container(key)
+    }
+
+    /// Lists every link within the database that doesn't resolve (dangling, cyclic, too deep or outside of the database)
+    pub fn check_links(&self) -> Result<Vec<BrokenLink>, LDBError> {
+        let root = self.as_container()?;
+        let mut broken = Vec::new();
+        for (location, key) in self.links()? {
+            let target = root.read_data(&key)?.collect_link_path()?;
+            match self.resolve_link(root.read_data(&key)?) {
+                Ok(_) => (),
+                Err(LDBError::IOError(e)) => return Err(LDBError::IOError(e)),
+                Err(error) => broken.push(BrokenLink { location, target, error }),
+            }
+        }
+        Ok(broken)
+    }
+
+    /// Rebuilds the reverse-reference index used by `LazyContainer::remove_with` from every link within the database
+    /// 
+    /// Only needed for links that weren't written through the database (like with `FileWrapper::new_atomic`)
+    pub fn reindex_links(&self) -> Result<(), LDBError> {
+        if self.is_read_only() { return Err(LDBError::ReadOnly(self.path().to_path_buf())) };
+        let _guard = LINK_LOCK.lock().unwrap();
+        let root = self.as_container()?;
+        let mut index = LazyMap::new();
+        for (_, key) in self.links()? {
+            let target = root.read_data(&key)?.collect_link_path()?;
+            let Ok(target) = self.links.target(&self.path().join(&key), &target) else { continue };
+            index.insert(key.to_string_lossy(), target.to_string_lossy().as_ref())?;
+        }
+        self.links.save(&**root.backend(), &index)
+    }
+
+    /// Rewrites the nested links of a database written by an older version of `lazy-db` (see `relative_links`) to start from the root,
+    /// so they still point to the same place
+    pub(crate) fn migrate_links(&self) -> Result<(), LDBError> {
+        let backend = self.backend();
+        for (_, key) in self.links()? {
+            let path = self.path().join(&key);
+            let target = LazyData::load_from(&*backend, &path)?.collect_link_path()?;
+            if target.has_root() || key.parent() == Some(Path::new("")) { continue }; // links at the root resolve the same either way
+            let writer = unwrap_result!((backend.open_writer(&path)) err => LDBError::IOError(err));
+            LazyData::new_link(writer, Path::new("/").join(target))?;
+        }
+        Ok(())
+    }
+
+    /// Finds the location and key (from the root) of every link within the database
+    fn links(&self) -> Result<Vec<(WalkEntry, PathBuf)>, LDBError> {
+        let mut links = Vec::new();
+        self.walk(WalkOrder::DepthFirst, |entry| {
+            if let (WalkKind::Data(LazyType::Link), Some(key)) = (&entry.kind, &entry.key) {
+                let path: PathBuf = entry.containers.iter().chain(Some(key)).collect();
+                let location = WalkEntry { containers: entry.containers.clone(), key: Some(key.clone()), kind: WalkKind::Data(LazyType::Link) };
+                links.push((location, path));
+            }
+            Ok(WalkControl::Continue)
+        })?;
+        Ok(links)
+    }
+}
+
+/// Converts the target of a link at a path into a key relative to the root of the database
+/// (resolving targets without a leading `/` from the root if links aren't `relative`)
+fn link_key(root: &Path, link: &Path, target: &Path, relative: bool) -> Result<PathBuf, LDBError> {
+    let mut key = PathBuf::new();
+    if relative && !target.has_root() {
+        // Relative to the link's container (or the root for links that aren't within the database)
+        if let Some(parent) = link.parent().and_then(|x| x.strip_prefix(root).ok()) { key.push(parent) };
+    }
+
+    for component in target.components() {
+        match component {
+            Component::Normal(x) => key.push(x),
+            Component::ParentDir if !key.pop() =>
+                return Err(LDBError::InvalidValue(LazyType::Link, format!("'{}' is outside of the database", target.to_string_lossy()))),
+            _ => (),
+        }
+    }
+    Ok(key)
+}
+
+/// How the links of a database resolve and the reverse-reference index of them (a `LazyMap` of each link's key to the key it points to),
+/// shared by the containers of the database
+pub(crate) struct Links {
+    root: PathBuf,
+    /// If links are resolved relative to their container (see `relative_links`)
+    relative: bool,
+}
+
+impl Links {
+    /// Constructs the links of a database of a version
+    #[inline]
+    pub(crate) fn new(root: impl AsRef<Path>, version: &version::Version) -> Self {
+        Self { root: root.as_ref().to_path_buf(), relative: relative_links(version) }
+    }
+
+    fn load(&self, backend: &dyn StorageBackend) -> Result<LazyMap, LDBError> {
+        let path = self.root.join(LINK_INDEX);
+        if backend.kind(&path) != Some(EntryKind::Data) { return Ok(LazyMap::new()) };
+        LazyData::load_from(backend, path)?.collect_map()
+    }
+
+    fn save(&self, backend: &dyn StorageBackend, index: &LazyMap) -> Result<(), LDBError> {
+        let file = unwrap_result!((backend.open_writer(&self.root.join(LINK_INDEX))) err => LDBError::IOError(err));
+        LazyData::new_map(file, index)
+    }
+
+    /// Converts a path into a key relative to the root of the database
+    #[inline]
+    fn key(&self, path: &Path) -> PathBuf {
+        path.strip_prefix(&self.root).unwrap_or(path).to_path_buf()
+    }
+
+    /// Resolves the key a link at a path points to (one hop), without reading anything
+    #[inline]
+    pub(crate) fn target(&self, link: &Path, target: &Path) -> Result<PathBuf, LDBError> {
+        link_key(&self.root, link, target, self.relative)
+    }
+
+    /// Records links (the path of each and the path it points to) in the index and only then writes them with `write`,
+    /// so the index holds every link written through the database (and maybe some that have since been removed or replaced)
+    pub(crate) fn record<T>(&self, backend: &dyn StorageBackend, links: &[(PathBuf, PathBuf)], write: impl FnOnce() -> Result<T, LDBError>) -> Result<T, LDBError> {
+        if links.is_empty() { return write() };
+        let _guard = LINK_LOCK.lock().unwrap();
+        let mut index = self.load(backend)?;
+        for (link, target) in links {
+            let key = self.key(link).to_string_lossy().to_string();
+            match self.target(link, target) {
+                Ok(target) => index.insert(key, target.to_string_lossy().as_ref())?,
+                Err(_) => { index.remove(&key); }, // outside of the database, so it never points to what's removed
+            }
+        }
+        self.save(backend, &index)?;
+        write()
+    }
+
+    /// Removes the data or container at a path as specified by the `RemovePolicy`, forgetting the links within it
+    /// 
+    /// The index is only read for `RemovePolicy::Restrict` and `RemovePolicy::Cascade`
+    pub(crate) fn remove(&self, backend: &dyn StorageBackend, path: &Path, policy: RemovePolicy) -> Result<(), LDBError> {
+        if policy == RemovePolicy::Ignore {
+            unwrap_result!((backend.remove(path)) err => LDBError::IOError(err));
+            return Ok(());
+        }
+
+        let _guard = LINK_LOCK.lock().unwrap();
+        let mut index = self.load(backend)?;
+        let len = index.len();
+        let links = self.indexed(backend, &mut index)?;
+
+        let mut queue = vec![self.key(path)];
+        let mut removed: Vec<PathBuf> = Vec::new();
+        while let Some(key) = queue.pop() {
+            if removed.iter().any(|x| key.starts_with(x)) { continue };
+            for (link, _) in links.iter().filter(|(link, target)| target.starts_with(&key) && !link.starts_with(&key)) {
+                match policy {
+                    RemovePolicy::Restrict => return Err(LDBError::StillLinked(self.root.join(link), path.to_path_buf())),
+                    _ => queue.push(link.clone()),
+                }
+            }
+            removed.push(key);
+        }
+
+        // Remove the links before what they point to
+        for key in removed.iter().skip(1) {
+            let path = self.root.join(key);
+            if backend.kind(&path).is_some() { unwrap_result!((backend.remove(&path)) err => LDBError::IOError(err)) };
+        }
+        unwrap_result!((backend.remove(path)) err => LDBError::IOError(err));
+
+        for key in removed {
+            let within: Vec<String> = index.keys().filter(|x| Path::new(x).starts_with(&key)).map(String::from).collect();
+            for link in within { index.remove(&link); };
+        }
+        if index.len() != len { self.save(backend, &index)? };
+        Ok(())
+    }
+
+    /// Reads the key of each indexed link and the key it points to, forgetting the links that no longer exist
+    fn indexed(&self, backend: &dyn StorageBackend, index: &mut LazyMap) -> Result<Vec<(PathBuf, PathBuf)>, LDBError> {
+        let mut links = Vec::new();
+        for link in index.keys().map(String::from).collect::<Vec<_>>() {
+            let path = self.root.join(&link);
+            let data = match backend.kind(&path) {
+                Some(EntryKind::Data) => LazyData::load_from(backend, &path)?,
+                _ => { index.remove(&link); continue },
+            };
+            if data.lazy_type != LazyType::Link { index.remove(&link); continue };
+
+            // The link may have been replaced since it was indexed (like by a write that failed)
+            match self.target(&path, &data.collect_link_path()?) {
+                Ok(target) => links.push((PathBuf::from(link), target)),
+                Err(_) => { index.remove(&link); },
+            }
+        }
+        Ok(links)
+    }
+}
+
+/// A writer of `LazyData` within a database that records it in the index of links (see `Links::record`) if it's a link
+pub(crate) struct LinkWriter {
+    inner: FileWrapper,
+    path: PathBuf,
+    backend: Arc<dyn StorageBackend>,
+    links: Arc<Links>,
+    /// Everything written so far, for as long as it could be a link
+    link: Option<Vec<u8>>,
+}
+
+impl LinkWriter {
+    #[inline]
+    pub(crate) fn new(inner: FileWrapper, path: PathBuf, backend: Arc<dyn StorageBackend>, links: Arc<Links>) -> Self {
+        Self { inner, path, backend, links, link: Some(Vec::new()) }
+    }
+}
+
+impl Write for LinkWriter {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, io::Error> {
+        let len = self.inner.writer().write(buf)?;
+        if let Some(bytes) = &mut self.link {
+            bytes.extend_from_slice(&buf[..len]);
+            if bytes.first().is_some_and(|x| *x != LazyType::Link.into()) { self.link = None };
+        }
+        Ok(len)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<(), io::Error> {
+        self.inner.writer().flush()
+    }
+}
+
+impl StorageWriter for LinkWriter {
+    fn commit(self: Box<Self>) -> Result<(), io::Error> {
+        let Self { inner, path, backend, links, link } = *self;
+        let target = link.as_deref().filter(|x| !x.is_empty()).and_then(|x| std::str::from_utf8(&x[1..]).ok());
+        let recorded: Vec<(PathBuf, PathBuf)> = target.map(|x| (path, PathBuf::from(x))).into_iter().collect();
+        links.record(&*backend, &recorded, || inner.finish().map_err(LDBError::IOError))
+            .map_err(|e| match e {
+                LDBError::IOError(e) => e,
+                e => io::Error::other(e.to_string()),
+            })
+    }
+}
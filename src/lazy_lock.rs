@@ -0,0 +1,115 @@
+use crate::*;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+/// The name of the lock file of a database directory (next to `.meta`)
+pub(crate) const LOCK_FILE: &str = ".lock";
+
+/// How a `LazyDB` is locked against other processes
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LockMode {
+    /// Only one process can hold the lock (for writing)
+    Exclusive,
+    /// Many processes can hold the lock at once, but not while one holds it exclusively (for reading)
+    Shared,
+}
+
+/// What to do when the lock of a `LazyDB` is held by another process
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LockWait {
+    /// Fail straight away with `LDBError::DatabaseLocked`
+    Try,
+    /// Wait until the lock is released
+    Block,
+    /// Wait until the lock is released, failing with `LDBError::DatabaseLocked` after a timeout
+    Timeout(Duration),
+}
+
+/// An advisory lock on a lock file, held until it's dropped
+pub(crate) struct DbLock {
+    _file: File,
+    path: PathBuf,
+    mode: LockMode,
+}
+
+/// The locks held by this process, so that a process never blocks on itself
+static HELD: Mutex<Vec<Weak<DbLock>>> = Mutex::new(Vec::new());
+
+impl DbLock {
+    /// Acquires the lock of a lock file (creating it if it doesn't exist)
+    ///
+    /// Locks already held by this process are shared, unless a shared lock would need to become exclusive
+    pub(crate) fn acquire(path: impl AsRef<Path>, mode: LockMode, wait: LockWait) -> Result<Arc<Self>, LDBError> {
+        let path = canonical(path.as_ref())?;
+        if let Some(lock) = Self::held(&path, mode)? { return Ok(lock) };
+
+        // Only creates (and writes to) the lock file if it doesn't exist, so existing locks work on read-only media
+        let file = match File::open(&path) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => OpenOptions::new().create(true).truncate(false).write(true).open(&path),
+            x => x,
+        };
+        let file = unwrap_result!((file) err => LDBError::IOError(err));
+        let try_lock = || match mode {
+            LockMode::Exclusive => file.try_lock(),
+            LockMode::Shared => file.try_lock_shared(),
+        };
+
+        // `HELD` isn't locked while waiting, so other databases of this process aren't held up
+        match wait {
+            LockWait::Block => {
+                let result = match mode {
+                    LockMode::Exclusive => file.lock(),
+                    LockMode::Shared => file.lock_shared(),
+                };
+                unwrap_result!((result) err => LDBError::IOError(err));
+            },
+            LockWait::Try => match try_lock() {
+                Ok(()) => (),
+                Err(TryLockError::WouldBlock) => return Err(LDBError::DatabaseLocked(path)),
+                Err(TryLockError::Error(e)) => return Err(LDBError::IOError(e)),
+            },
+            LockWait::Timeout(timeout) => {
+                let deadline = Instant::now() + timeout;
+                loop {
+                    match try_lock() {
+                        Ok(()) => break,
+                        Err(TryLockError::WouldBlock) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(10)),
+                        Err(TryLockError::WouldBlock) => return Err(LDBError::DatabaseLocked(path)),
+                        Err(TryLockError::Error(e)) => return Err(LDBError::IOError(e)),
+                    }
+                }
+            },
+        }
+
+        // Another thread of this process may have acquired the same lock while this one waited
+        let mut held = HELD.lock().unwrap();
+        if let Some(lock) = held.iter().filter_map(Weak::upgrade).find(|x| x.path == path && (x.mode == mode || mode == LockMode::Shared)) {
+            return Ok(lock);
+        }
+        let lock = Arc::new(Self { _file: file, path, mode });
+        held.push(Arc::downgrade(&lock));
+        Ok(lock)
+    }
+
+    /// Finds a lock already held by this process (returning `LDBError::DatabaseLocked` if a shared lock would need to become exclusive)
+    fn held(path: &Path, mode: LockMode) -> Result<Option<Arc<Self>>, LDBError> {
+        let mut held = HELD.lock().unwrap();
+        held.retain(|x| x.strong_count() > 0);
+        match held.iter().filter_map(Weak::upgrade).find(|x| x.path == path) {
+            Some(lock) if lock.mode == LockMode::Shared && mode == LockMode::Exclusive => Err(LDBError::DatabaseLocked(path.to_path_buf())),
+            x => Ok(x),
+        }
+    }
+}
+
+/// Makes the path of a (possibly non-existent) lock file absolute
+fn canonical(path: &Path) -> Result<PathBuf, LDBError> {
+    let parent = match path.parent() {
+        Some(x) if !x.as_os_str().is_empty() => x,
+        _ => Path::new("."),
+    };
+    let parent = unwrap_result!((parent.canonicalize()) err => LDBError::IOError(err));
+    Ok(parent.join(path.file_name().unwrap_or_default()))
+}
@@ -0,0 +1,55 @@
+//! Serde support for storing any `Serialize` type into a `LazyContainer` tree (requires the `serde` feature)
+//!
+//! ## Layout
+//! - Scalars (numbers, strings, bools, bytes, units) become `LazyData` files
+//! - Structs and maps become child containers with one key per field / entry
+//!   (map keys that are paths or start with `.` return `LDBError::InvalidKey`)
+//! - Sequences and tuples become child containers with numbered keys (`0`, `1`, ...)
+//! - Unit enum variants become strings while other variants become a child container holding a single key (the variant name)
+//! - `None` becomes `LazyType::Void`
+
+mod serializer;
+mod deserializer;
+
+pub use serializer::*;
+pub use deserializer::*;
+
+use crate::*;
+use std::fmt::Display;
+use serde::{Serialize, de::DeserializeOwned};
+
+impl serde::ser::Error for LDBError {
+    fn custom<T: Display>(msg: T) -> Self {
+        LDBError::SerdeError(msg.to_string())
+    }
+}
+
+impl serde::de::Error for LDBError {
+    fn custom<T: Display>(msg: T) -> Self {
+        LDBError::SerdeError(msg.to_string())
+    }
+}
+
+/// Serializes a struct or map directly into a `LazyContainer` (one key per field)
+///
+/// Keys that already exist in the container are replaced while other keys are left untouched
+pub fn to_container<T: Serialize + ?Sized>(container: &LazyContainer, value: &T) -> Result<(), LDBError> {
+    value.serialize(LazySerializer::root(container))
+}
+
+/// Serializes any value into a key of a `LazyContainer`
+///
+/// If the key already exists (as data or a container) it will be replaced
+pub fn to_key<T: Serialize + ?Sized>(container: &LazyContainer, key: &str, value: &T) -> Result<(), LDBError> {
+    value.serialize(LazySerializer::key(container, key))
+}
+
+/// Deserializes a struct or map from the contents of a `LazyContainer`
+pub fn from_container<T: DeserializeOwned>(container: &LazyContainer) -> Result<T, LDBError> {
+    T::deserialize(LazyDeserializer::container(container.clone()))
+}
+
+/// Deserializes any value from a key of a `LazyContainer`
+pub fn from_key<T: DeserializeOwned>(container: &LazyContainer, key: &str) -> Result<T, LDBError> {
+    T::deserialize(LazyDeserializer::key(container, key)?)
+}
@@ -0,0 +1,279 @@
+use super::*;
+use serde::de::{self, Deserializer, Visitor, DeserializeSeed, IntoDeserializer};
+use serde::forward_to_deserialize_any;
+
+/// A `serde::Deserializer` that reads values from a `LazyContainer`
+pub enum LazyDeserializer {
+    Data(LazyData),
+    Container(LazyContainer),
+}
+
+impl LazyDeserializer {
+    /// A deserializer that reads a struct or map from the contents of a container
+    #[inline]
+    pub fn container(container: LazyContainer) -> Self {
+        Self::Container(container)
+    }
+
+    /// A deserializer that reads any value at a key of a container
+    pub fn key(container: &LazyContainer, key: &str) -> Result<Self, LDBError> {
+        if container.contains_container(key) {
+            Ok(Self::Container(container.read_container(key)?))
+        } else {
+            Ok(Self::Data(container.read_data(key)?))
+        }
+    }
+
+    /// Checks if a key exists (as either data or a container)
+    fn exists(container: &LazyContainer, key: &str) -> bool {
+        container.contains_data(key) || container.contains_container(key)
+    }
+
+    fn incorrect_type(self, expected: &str) -> LDBError {
+        match self {
+            Self::Data(x) => LDBError::IncorrectType(x.lazy_type, expected.to_string()),
+            Self::Container(_) => LDBError::SerdeError(format!("expected {expected}, found a container")),
+        }
+    }
+}
+
+impl<'de> Deserializer<'de> for LazyDeserializer {
+    type Error = LDBError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, LDBError> {
+        let data = match self {
+            Self::Data(x) => x,
+            Self::Container(_) => return self.deserialize_map(visitor),
+        };
+
+        use LazyType as T;
+        match data.lazy_type {
+            T::Void => visitor.visit_unit(),
+            T::True | T::False => visitor.visit_bool(data.collect_bool()?),
+            T::String => visitor.visit_string(data.collect_string()?),
+            T::Binary => visitor.visit_byte_buf(data.collect_binary()?.into_vec()),
+            T::U8 => visitor.visit_u8(data.collect_u8()?),
+            T::U16 => visitor.visit_u16(data.collect_u16()?),
+            T::U32 => visitor.visit_u32(data.collect_u32()?),
+            T::U64 => visitor.visit_u64(data.collect_u64()?),
+            T::U128 => visitor.visit_u128(data.collect_u128()?),
+            T::I8 => visitor.visit_i8(data.collect_i8()?),
+            T::I16 => visitor.visit_i16(data.collect_i16()?),
+            T::I32 => visitor.visit_i32(data.collect_i32()?),
+            T::I64 => visitor.visit_i64(data.collect_i64()?),
+            T::I128 => visitor.visit_i128(data.collect_i128()?),
+            T::F32 => visitor.visit_f32(data.collect_f32()?),
+            T::F64 => visitor.visit_f64(data.collect_f64()?),
+            t => Err(LDBError::IncorrectType(t, String::from("serde value"))),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, LDBError> {
+        match &self {
+            Self::Data(x) if x.lazy_type == LazyType::Void => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, LDBError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, LDBError> {
+        match self {
+            Self::Container(container) => visitor.visit_seq(SeqAccess { container, index: 0 }),
+            _ => Err(self.incorrect_type("sequence")),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, LDBError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(self, _name: &'static str, _len: usize, visitor: V) -> Result<V::Value, LDBError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, LDBError> {
+        match self {
+            Self::Container(container) => {
+                let keys: Vec<String> = container.keys()?.collect();
+                visitor.visit_map(MapAccess { container, keys: keys.into_iter(), value: None })
+            },
+            _ => Err(self.incorrect_type("map")),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(self, _name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, LDBError> {
+        match self {
+            Self::Container(container) => {
+                // Only the fields that exist are visited; missing `Option`s become `None`
+                let keys: Vec<String> = fields.iter()
+                    .filter(|x| Self::exists(&container, x))
+                    .map(|x| x.to_string())
+                    .collect();
+                visitor.visit_map(MapAccess { container, keys: keys.into_iter(), value: None })
+            },
+            _ => Err(self.incorrect_type("struct")),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, LDBError> {
+        match self {
+            Self::Data(x) if x.lazy_type == LazyType::String => visitor.visit_enum(x.collect_string()?.into_deserializer()),
+            Self::Container(container) => {
+                let mut keys: Vec<String> = container.keys()?.collect();
+                if keys.len() != 1 {
+                    return Err(LDBError::SerdeError(format!("expected a single enum variant, found {} keys", keys.len())));
+                }
+                let variant = keys.remove(0);
+                let payload = Self::key(&container, &variant)?;
+                visitor.visit_enum(EnumAccess { variant, payload })
+            },
+            _ => Err(self.incorrect_type("enum")),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, LDBError> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct identifier
+    }
+}
+
+/// Reads the numbered keys of a container as a sequence
+struct SeqAccess {
+    container: LazyContainer,
+    index: usize,
+}
+
+impl<'de> de::SeqAccess<'de> for SeqAccess {
+    type Error = LDBError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, LDBError> {
+        let key = self.index.to_string();
+        if !LazyDeserializer::exists(&self.container, &key) { return Ok(None) };
+        self.index += 1;
+        seed.deserialize(LazyDeserializer::key(&self.container, &key)?).map(Some)
+    }
+}
+
+/// Reads the keys of a container as a map
+struct MapAccess {
+    container: LazyContainer,
+    keys: std::vec::IntoIter<String>,
+    value: Option<String>,
+}
+
+impl<'de> de::MapAccess<'de> for MapAccess {
+    type Error = LDBError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, LDBError> {
+        let key = match self.keys.next() {
+            Some(x) => x,
+            None => return Ok(None),
+        };
+        self.value = Some(key.clone());
+        seed.deserialize(MapKeyDeserializer(key)).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, LDBError> {
+        let key = match self.value.take() {
+            Some(x) => x,
+            None => return Err(LDBError::SerdeError(String::from("map value deserialized before it's key"))),
+        };
+        seed.deserialize(LazyDeserializer::key(&self.container, &key)?)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.keys.len())
+    }
+}
+
+/// Reads the variant name (key) and payload of an enum stored as a container
+struct EnumAccess {
+    variant: String,
+    payload: LazyDeserializer,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumAccess {
+    type Error = LDBError;
+    type Variant = LazyDeserializer;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, LazyDeserializer), LDBError> {
+        let variant = seed.deserialize(MapKeyDeserializer(self.variant))?;
+        Ok((variant, self.payload))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for LazyDeserializer {
+    type Error = LDBError;
+
+    fn unit_variant(self) -> Result<(), LDBError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, LDBError> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, LDBError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value, LDBError> {
+        self.deserialize_struct("", fields, visitor)
+    }
+}
+
+/// Reads the string keys of a `LazyContainer` as map keys (parsing them if needed)
+struct MapKeyDeserializer(String);
+
+macro_rules! parse_key {
+    ($($name:ident => $visit:ident;)*) => {$(
+        fn $name<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, LDBError> {
+            match self.0.parse() {
+                Ok(x) => visitor.$visit(x),
+                Err(_) => Err(LDBError::SerdeError(format!("invalid map key '{}'", self.0))),
+            }
+        }
+    )*};
+}
+
+impl<'de> Deserializer<'de> for MapKeyDeserializer {
+    type Error = LDBError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, LDBError> {
+        visitor.visit_string(self.0)
+    }
+
+    parse_key! {
+        deserialize_bool => visit_bool;
+        deserialize_i8 => visit_i8;
+        deserialize_i16 => visit_i16;
+        deserialize_i32 => visit_i32;
+        deserialize_i64 => visit_i64;
+        deserialize_i128 => visit_i128;
+        deserialize_u8 => visit_u8;
+        deserialize_u16 => visit_u16;
+        deserialize_u32 => visit_u32;
+        deserialize_u64 => visit_u64;
+        deserialize_u128 => visit_u128;
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, LDBError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, LDBError> {
+        visitor.visit_enum(self.0.into_deserializer())
+    }
+
+    forward_to_deserialize_any! {
+        f32 f64 char str string bytes byte_buf option unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
@@ -0,0 +1,305 @@
+use super::*;
+use serde::ser::{self, Serializer, Impossible};
+
+/// A `serde::Serializer` that writes values into a `LazyContainer`
+pub struct LazySerializer<'a> {
+    container: &'a LazyContainer,
+    key: Option<String>,
+}
+
+impl<'a> LazySerializer<'a> {
+    /// A serializer that writes a struct or map directly into the container
+    #[inline]
+    pub fn root(container: &'a LazyContainer) -> Self {
+        Self { container, key: None }
+    }
+
+    /// A serializer that writes any value at a key of the container
+    #[inline]
+    pub fn key(container: &'a LazyContainer, key: impl Into<String>) -> Self {
+        Self { container, key: Some(key.into()) }
+    }
+
+    /// Gets the key to write a scalar to
+    fn data_key(&self) -> Result<&str, LDBError> {
+        match &self.key {
+            Some(x) => Ok(x),
+            None => Err(LDBError::SerdeError(String::from("only structs and maps can be serialized into the root of a container"))),
+        }
+    }
+
+    /// Writes a scalar value at the key (replacing a container if there is one)
+    fn write<T: ToLazy>(self, value: T) -> Result<(), LDBError> {
+        let key = self.data_key()?;
+        if self.container.contains_container(key) {
+            self.container.remove(key)?;
+        }
+        self.container.write(key, value)
+    }
+
+    /// Gets the container that a compound value is written into
+    fn compound(&self) -> Result<LazyContainer, LDBError> {
+        match &self.key {
+            Some(x) => {
+                if self.container.contains_data(x) {
+                    self.container.remove(x)?;
+                }
+                self.container.new_container(x)
+            },
+            None => Ok(self.container.clone()),
+        }
+    }
+
+    /// Gets the container that the payload of an enum variant is written into
+    fn variant(&self, variant: &str) -> Result<LazyContainer, LDBError> {
+        self.data_key()?;
+        self.compound()?.new_container(variant)
+    }
+}
+
+/// Serializes the elements / fields of a compound value into a `LazyContainer`
+pub struct Compound {
+    container: LazyContainer,
+    index: usize,
+    key: Option<String>,
+}
+
+impl Compound {
+    #[inline]
+    fn new(container: LazyContainer) -> Self {
+        Self { container, index: 0, key: None }
+    }
+
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), LDBError> {
+        let key = self.index.to_string();
+        self.index += 1;
+        value.serialize(LazySerializer::key(&self.container, key))
+    }
+}
+
+impl<'a> Serializer for LazySerializer<'a> {
+    type Ok = ();
+    type Error = LDBError;
+    type SerializeSeq = Compound;
+    type SerializeTuple = Compound;
+    type SerializeTupleStruct = Compound;
+    type SerializeTupleVariant = Compound;
+    type SerializeMap = Compound;
+    type SerializeStruct = Compound;
+    type SerializeStructVariant = Compound;
+
+    fn serialize_bool(self, v: bool) -> Result<(), LDBError> { self.write(v) }
+    fn serialize_i8(self, v: i8) -> Result<(), LDBError> { self.write(v) }
+    fn serialize_i16(self, v: i16) -> Result<(), LDBError> { self.write(v) }
+    fn serialize_i32(self, v: i32) -> Result<(), LDBError> { self.write(v) }
+    fn serialize_i64(self, v: i64) -> Result<(), LDBError> { self.write(v) }
+    fn serialize_i128(self, v: i128) -> Result<(), LDBError> { self.write(v) }
+    fn serialize_u8(self, v: u8) -> Result<(), LDBError> { self.write(v) }
+    fn serialize_u16(self, v: u16) -> Result<(), LDBError> { self.write(v) }
+    fn serialize_u32(self, v: u32) -> Result<(), LDBError> { self.write(v) }
+    fn serialize_u64(self, v: u64) -> Result<(), LDBError> { self.write(v) }
+    fn serialize_u128(self, v: u128) -> Result<(), LDBError> { self.write(v) }
+    fn serialize_f32(self, v: f32) -> Result<(), LDBError> { self.write(v) }
+    fn serialize_f64(self, v: f64) -> Result<(), LDBError> { self.write(v) }
+    fn serialize_char(self, v: char) -> Result<(), LDBError> { self.write(v.to_string()) }
+    fn serialize_str(self, v: &str) -> Result<(), LDBError> { self.write(v) }
+    fn serialize_none(self) -> Result<(), LDBError> { self.write(()) }
+    fn serialize_unit(self) -> Result<(), LDBError> { self.write(()) }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), LDBError> { self.write(()) }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), LDBError> {
+        let key = self.data_key()?;
+        if self.container.contains_container(key) {
+            self.container.remove(key)?;
+        }
+        LazyData::new_binary(self.container.data_writer(key)?, v)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), LDBError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<(), LDBError> {
+        self.write(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<(), LDBError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _name: &'static str, _index: u32, variant: &'static str, value: &T) -> Result<(), LDBError> {
+        self.data_key()?;
+        let container = self.compound()?;
+        value.serialize(LazySerializer::key(&container, variant))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound, LDBError> {
+        self.data_key()?;
+        Ok(Compound::new(self.compound()?))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Compound, LDBError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<Compound, LDBError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(self, _name: &'static str, _index: u32, variant: &'static str, _len: usize) -> Result<Compound, LDBError> {
+        Ok(Compound::new(self.variant(variant)?))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound, LDBError> {
+        Ok(Compound::new(self.compound()?))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Compound, LDBError> {
+        Ok(Compound::new(self.compound()?))
+    }
+
+    fn serialize_struct_variant(self, _name: &'static str, _index: u32, variant: &'static str, _len: usize) -> Result<Compound, LDBError> {
+        Ok(Compound::new(self.variant(variant)?))
+    }
+}
+
+impl ser::SerializeSeq for Compound {
+    type Ok = ();
+    type Error = LDBError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), LDBError> { self.element(value) }
+    fn end(self) -> Result<(), LDBError> { Ok(()) }
+}
+
+impl ser::SerializeTuple for Compound {
+    type Ok = ();
+    type Error = LDBError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), LDBError> { self.element(value) }
+    fn end(self) -> Result<(), LDBError> { Ok(()) }
+}
+
+impl ser::SerializeTupleStruct for Compound {
+    type Ok = ();
+    type Error = LDBError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), LDBError> { self.element(value) }
+    fn end(self) -> Result<(), LDBError> { Ok(()) }
+}
+
+impl ser::SerializeTupleVariant for Compound {
+    type Ok = ();
+    type Error = LDBError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), LDBError> { self.element(value) }
+    fn end(self) -> Result<(), LDBError> { Ok(()) }
+}
+
+impl ser::SerializeMap for Compound {
+    type Ok = ();
+    type Error = LDBError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), LDBError> {
+        let key = key.serialize(MapKeySerializer)?;
+        crate::lazy_container::check_key(&key)?;
+        self.key = Some(key);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), LDBError> {
+        let key = match self.key.take() {
+            Some(x) => x,
+            None => return Err(LDBError::SerdeError(String::from("map value serialized before it's key"))),
+        };
+        value.serialize(LazySerializer::key(&self.container, key))
+    }
+
+    fn end(self) -> Result<(), LDBError> { Ok(()) }
+}
+
+impl ser::SerializeStruct for Compound {
+    type Ok = ();
+    type Error = LDBError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), LDBError> {
+        value.serialize(LazySerializer::key(&self.container, key))
+    }
+
+    fn end(self) -> Result<(), LDBError> { Ok(()) }
+}
+
+impl ser::SerializeStructVariant for Compound {
+    type Ok = ();
+    type Error = LDBError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), LDBError> {
+        value.serialize(LazySerializer::key(&self.container, key))
+    }
+
+    fn end(self) -> Result<(), LDBError> { Ok(()) }
+}
+
+/// Converts map keys into the string keys of a `LazyContainer`
+struct MapKeySerializer;
+
+macro_rules! key_to_string {
+    ($($name:ident($type:ty);)*) => {$(
+        fn $name(self, v: $type) -> Result<String, LDBError> { Ok(v.to_string()) }
+    )*};
+}
+
+fn invalid_key() -> LDBError {
+    LDBError::SerdeError(String::from("map keys must be strings, chars, bools or integers"))
+}
+
+impl Serializer for MapKeySerializer {
+    type Ok = String;
+    type Error = LDBError;
+    type SerializeSeq = Impossible<String, LDBError>;
+    type SerializeTuple = Impossible<String, LDBError>;
+    type SerializeTupleStruct = Impossible<String, LDBError>;
+    type SerializeTupleVariant = Impossible<String, LDBError>;
+    type SerializeMap = Impossible<String, LDBError>;
+    type SerializeStruct = Impossible<String, LDBError>;
+    type SerializeStructVariant = Impossible<String, LDBError>;
+
+    key_to_string! {
+        serialize_bool(bool);
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_i128(i128);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_u128(u128);
+        serialize_char(char);
+        serialize_str(&str);
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<String, LDBError> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<String, LDBError> {
+        value.serialize(self)
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<String, LDBError> { Err(invalid_key()) }
+    fn serialize_f64(self, _v: f64) -> Result<String, LDBError> { Err(invalid_key()) }
+    fn serialize_bytes(self, _v: &[u8]) -> Result<String, LDBError> { Err(invalid_key()) }
+    fn serialize_none(self) -> Result<String, LDBError> { Err(invalid_key()) }
+    fn serialize_some<T: Serialize + ?Sized>(self, _value: &T) -> Result<String, LDBError> { Err(invalid_key()) }
+    fn serialize_unit(self) -> Result<String, LDBError> { Err(invalid_key()) }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String, LDBError> { Err(invalid_key()) }
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _name: &'static str, _index: u32, _variant: &'static str, _value: &T) -> Result<String, LDBError> { Err(invalid_key()) }
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, LDBError> { Err(invalid_key()) }
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, LDBError> { Err(invalid_key()) }
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct, LDBError> { Err(invalid_key()) }
+    fn serialize_tuple_variant(self, _name: &'static str, _index: u32, _variant: &'static str, _len: usize) -> Result<Self::SerializeTupleVariant, LDBError> { Err(invalid_key()) }
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, LDBError> { Err(invalid_key()) }
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, LDBError> { Err(invalid_key()) }
+    fn serialize_struct_variant(self, _name: &'static str, _index: u32, _variant: &'static str, _len: usize) -> Result<Self::SerializeStructVariant, LDBError> { Err(invalid_key()) }
+}
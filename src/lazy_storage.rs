@@ -0,0 +1,257 @@
+use crate::*;
+use crate::lazy_wal::Wal;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::fs;
+
+mod memory;
+mod archive;
+pub use memory::*;
+pub use archive::*;
+
+/// The kind of an entry within a `StorageBackend`
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum EntryKind {
+    /// A `LazyData` file
+    Data,
+    /// A `LazyContainer` directory
+    Container,
+}
+
+/// A writer returned by `StorageBackend::open_writer`
+///
+/// Nothing written should be visible at the writer's path until it's committed
+pub trait StorageWriter: Write + Send {
+    /// Makes everything written visible at the writer's path (replacing what was there before)
+    fn commit(self: Box<Self>) -> Result<(), io::Error>;
+}
+
+/// The store that `LazyContainer`s and `LazyData` live in
+///
+/// Paths are `/`-separated like filesystem paths; `FsBackend` (the filesystem) is the default
+pub trait StorageBackend: Send + Sync {
+    /// Opens a `FileWrapper` for reading the data at a path
+    fn open_reader(&self, path: &Path) -> Result<FileWrapper, io::Error>;
+
+    /// Opens a `FileWrapper` for (atomically) replacing the data at a path once it's finished
+    fn open_writer(&self, path: &Path) -> Result<FileWrapper, io::Error>;
+
+    /// Lists the names and kinds of the entries within a directory (in any order)
+    fn list(&self, path: &Path) -> Result<Vec<(String, EntryKind)>, io::Error>;
+
+    /// Gets the kind of the entry at a path, if it exists
+    fn kind(&self, path: &Path) -> Option<EntryKind>;
+
+    /// Removes the data or directory (and everything within it) at a path
+    fn remove(&self, path: &Path) -> Result<(), io::Error>;
+
+    /// Creates a directory (and any missing parent directories) if it doesn't exist
+    fn create_dir(&self, path: &Path) -> Result<(), io::Error>;
+
+    /// Moves the data or directory at a path to another path (replacing any data or directory there)
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), io::Error>;
+
+    /// Overwrites the bytes of the data at a path from an offset (extending it if needed)
+    /// 
+    /// Must be as crash-safe as `open_writer`; by default the data is read, modified and then replaced with `open_writer`
+    #[inline]
+    fn write_at(&self, path: &Path, offset: u64, bytes: &[u8]) -> Result<(), io::Error> {
+        rewrite(self, path, |data| splice(data, offset, bytes))
+    }
+
+    /// Shortens the data at a path to a length in bytes
+    /// 
+    /// Must be as crash-safe as `open_writer`; by default the data is read, shortened and then replaced with `open_writer`
+    #[inline]
+    fn truncate(&self, path: &Path, len: u64) -> Result<(), io::Error> {
+        rewrite(self, path, |data| data.truncate(len as usize))
+    }
+
+    /// Replaces the directory at a path with a new, empty one
+    fn replace_dir(&self, path: &Path) -> Result<(), io::Error> {
+        self.remove(path)?;
+        self.create_dir(path)
+    }
+
+    /// Removes everything within the directory at a path
+    fn clear_dir(&self, path: &Path) -> Result<(), io::Error> {
+        for (name, _) in self.list(path)? { self.remove(&path.join(name))? };
+        Ok(())
+    }
+}
+
+/// Reads the data at a path, modifies it and then atomically replaces it with `StorageBackend::open_writer`
+fn rewrite<B: StorageBackend + ?Sized>(backend: &B, path: &Path, modify: impl FnOnce(&mut Vec<u8>)) -> Result<(), io::Error> {
+    let mut data = Vec::new();
+    backend.open_reader(path)?.reader().read_to_end(&mut data)?;
+    modify(&mut data);
+
+    let mut writer = backend.open_writer(path)?;
+    writer.writer().write_all(&data)?;
+    writer.finish()
+}
+
+/// Overwrites bytes from an offset (extending the data if needed)
+fn splice(data: &mut Vec<u8>, offset: u64, bytes: &[u8]) {
+    let (start, end) = (offset as usize, offset as usize + bytes.len());
+    if end > data.len() { data.resize(end, 0) };
+    data[start..end].copy_from_slice(bytes);
+}
+
+/// The filesystem `StorageBackend` (directories are containers and files are `LazyData`)
+#[derive(Default, Clone)]
+pub struct FsBackend {
+    wal: Option<Arc<Wal>>,
+}
+
+impl FsBackend {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Constructs a filesystem backend that logs it's mutations to a write-ahead log (if there is one)
+    #[inline]
+    pub(crate) fn logged(wal: Option<Arc<Wal>>) -> Self {
+        Self { wal }
+    }
+
+    /// Runs a mutation, logging it to the write-ahead log (if there is one) before it's applied
+    fn log<T>(&self, begin: impl FnOnce(&Wal) -> Result<u64, io::Error>, mutation: impl FnOnce() -> Result<T, io::Error>) -> Result<T, io::Error> {
+        let id = match &self.wal { Some(wal) => Some((wal, begin(wal)?)), None => None };
+        let result = mutation();
+        if let Some((wal, id)) = id { wal.end(id)? };
+        result
+    }
+}
+
+impl StorageBackend for FsBackend {
+    fn open_reader(&self, path: &Path) -> Result<FileWrapper, io::Error> {
+        Ok(FileWrapper::new_reader(fs::File::open(path)?))
+    }
+
+    fn open_writer(&self, path: &Path) -> Result<FileWrapper, io::Error> {
+        FileWrapper::new_atomic_logged(path, self.wal.as_ref())
+    }
+
+    fn list(&self, path: &Path) -> Result<Vec<(String, EntryKind)>, io::Error> {
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            if file_type.is_dir() { entries.push((name, EntryKind::Container)) }
+            else if file_type.is_file() { entries.push((name, EntryKind::Data)) };
+        }
+        Ok(entries)
+    }
+
+    fn kind(&self, path: &Path) -> Option<EntryKind> {
+        let metadata = fs::metadata(path).ok()?;
+        if metadata.is_dir() { Some(EntryKind::Container) }
+        else if metadata.is_file() { Some(EntryKind::Data) }
+        else { None }
+    }
+
+    fn remove(&self, path: &Path) -> Result<(), io::Error> {
+        self.log(|wal| wal.begin_remove(path), || {
+            if path.is_dir() {
+                fs::remove_dir_all(path)?;
+            } else {
+                fs::remove_file(path)?;
+            }
+            sync_parent(path)
+        })
+    }
+
+    fn create_dir(&self, path: &Path) -> Result<(), io::Error> {
+        fs::create_dir_all(path)
+    }
+
+    /// Swaps the entries atomically (`renameat2(RENAME_EXCHANGE)`) where supported if either is a directory, otherwise with two renames;
+    /// either way the old entry is only removed once the new one is in place (and leftovers are removed by `LazyDB::load_dir`)
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), io::Error> {
+        let swap = fs::symlink_metadata(to).is_ok_and(|x| x.is_dir() || from.is_dir());
+        if !swap {
+            fs::rename(from, to)?;
+            return sync_parent(to);
+        }
+
+        if exchange(from, to)? {
+            sync_parent(to)?;
+            return lazy_wal::remove_any(from); // now holds the old entry
+        }
+
+        let old = hidden_sibling(to, "old");
+        fs::rename(to, &old)?;
+        fs::rename(from, to)?;
+        sync_parent(to)?;
+        lazy_wal::remove_any(&old)
+    }
+
+    /// Writes to the file in place if there's a write-ahead log (which logs the bytes first, so an interrupted write is redone),
+    /// otherwise rewrites the file atomically like the default `StorageBackend::write_at`
+    fn write_at(&self, path: &Path, offset: u64, bytes: &[u8]) -> Result<(), io::Error> {
+        if self.wal.is_none() { return rewrite(self, path, |data| splice(data, offset, bytes)) };
+        self.log(|wal| wal.begin_write_at(path, offset, bytes), || lazy_wal::write_at(path, offset, bytes))
+    }
+
+    /// Shortens the file in place if there's a write-ahead log (so an interrupted truncation is redone),
+    /// otherwise rewrites the file atomically like the default `StorageBackend::truncate`
+    fn truncate(&self, path: &Path, len: u64) -> Result<(), io::Error> {
+        if self.wal.is_none() { return rewrite(self, path, |data| data.truncate(len as usize)) };
+        self.log(|wal| wal.begin_truncate(path, len), || lazy_wal::truncate(path, len))
+    }
+
+    /// Builds the new directory next to the old one and swaps them atomically (`renameat2(RENAME_EXCHANGE)`) where supported,
+    /// otherwise with two renames; either way it's never left partially wiped (and leftovers are removed by `LazyDB::load_dir`)
+    fn replace_dir(&self, path: &Path) -> Result<(), io::Error> {
+        let tmp = hidden_sibling(path, "tmp");
+        let old = hidden_sibling(path, "old");
+        self.log(|wal| wal.begin_new_container(&tmp, &old, path), || {
+            fs::create_dir(&tmp)?;
+            if exchange(&tmp, path)? {
+                sync_parent(path)?;
+                return fs::remove_dir_all(&tmp); // now holds the old directory
+            }
+
+            fs::rename(path, &old)?;
+            fs::rename(&tmp, path)?;
+            sync_parent(path)?;
+
+            // Wipe the old directory
+            fs::remove_dir_all(&old)
+        })
+    }
+
+    fn clear_dir(&self, path: &Path) -> Result<(), io::Error> {
+        self.log(|wal| wal.begin_wipe(path), || {
+            fs::remove_dir_all(path)?;
+            fs::create_dir_all(path)
+        })
+    }
+}
+
+/// Atomically swaps two paths, returning `false` if the platform or filesystem doesn't support it
+#[cfg(target_os = "linux")]
+fn exchange(a: &Path, b: &Path) -> Result<bool, io::Error> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    let (a, b) = (CString::new(a.as_os_str().as_bytes())?, CString::new(b.as_os_str().as_bytes())?);
+    // SAFETY: both paths are valid, nul-terminated strings that outlive the call
+    let result = unsafe { libc::renameat2(libc::AT_FDCWD, a.as_ptr(), libc::AT_FDCWD, b.as_ptr(), libc::RENAME_EXCHANGE) };
+    if result == 0 { return Ok(true) };
+    let err = io::Error::last_os_error();
+    match err.raw_os_error() {
+        Some(libc::EINVAL | libc::ENOSYS | libc::EOPNOTSUPP) => Ok(false),
+        _ => Err(err),
+    }
+}
+
+/// Atomically swaps two paths, returning `false` if the platform or filesystem doesn't support it
+#[cfg(not(target_os = "linux"))]
+#[inline]
+fn exchange(_a: &Path, _b: &Path) -> Result<bool, io::Error> {
+    Ok(false)
+}
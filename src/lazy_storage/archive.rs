@@ -0,0 +1,90 @@
+use super::*;
+use crate::lazy_archive;
+use std::collections::BTreeMap;
+use std::io::Cursor;
+use std::ops::Bound;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// A read-only `StorageBackend` that reads `LazyData` directly out of a compiled (indexed) `.ldb` file
+/// 
+/// Only the index is read up-front; each `LazyData` is read and decompressed on it's own when it's opened.
+/// Paths are rooted at `/`
+pub struct ArchiveBackend {
+    file: Mutex<fs::File>,
+    index: BTreeMap<PathBuf, lazy_archive::IndexEntry>,
+}
+
+impl ArchiveBackend {
+    /// Opens a compiled `.ldb` file and reads it's index
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, LDBError> {
+        let path = path.as_ref();
+        if !path.is_file() { return Err(LDBError::FileNotFound(path.to_path_buf())) };
+        let mut file = unwrap_result!((fs::File::open(path)) err => LDBError::IOError(err));
+        let index = unwrap_result!((lazy_archive::read_index(&mut file)) err => LDBError::IOError(err));
+
+        Ok(Self {
+            file: Mutex::new(file),
+            index: std::iter::once((PathBuf::from("/"), None))
+                .chain(index.into_iter().map(|(x, entry)| (Path::new("/").join(x), entry)))
+                .collect(),
+        })
+    }
+
+    /// Checks if a file is a compiled `.ldb` file that can be read directly (older versions of `lazy-db` compiled to a different format)
+    pub fn is_indexed(path: impl AsRef<Path>) -> bool {
+        match fs::File::open(path) {
+            Ok(mut file) => lazy_archive::is_indexed(&mut file).unwrap_or(false),
+            Err(_) => false,
+        }
+    }
+}
+
+fn read_only(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::ReadOnlyFilesystem, format!("cannot modify '{}' within a compiled archive", path.to_string_lossy()))
+}
+
+impl StorageBackend for ArchiveBackend {
+    fn open_reader(&self, path: &Path) -> Result<FileWrapper, io::Error> {
+        match self.index.get(path) {
+            Some(Some((offset, len))) => {
+                let data = lazy_archive::read_block(&mut self.file.lock().unwrap(), *offset, *len)?;
+                Ok(FileWrapper::from_reader(Cursor::new(data)))
+            },
+            _ => Err(io::Error::new(io::ErrorKind::NotFound, format!("'{}' not found", path.to_string_lossy()))),
+        }
+    }
+
+    fn open_writer(&self, path: &Path) -> Result<FileWrapper, io::Error> {
+        Err(read_only(path))
+    }
+
+    fn list(&self, path: &Path) -> Result<Vec<(String, EntryKind)>, io::Error> {
+        Ok(self.index.range::<Path, _>((Bound::Excluded(path), Bound::Unbounded))
+            .take_while(|(x, _)| x.starts_with(path))
+            .filter(|(x, _)| x.parent() == Some(path))
+            .map(|(x, entry)| {
+                let name = x.file_name().unwrap_or_default().to_string_lossy().to_string();
+                (name, if entry.is_some() { EntryKind::Data } else { EntryKind::Container })
+            }).collect())
+    }
+
+    fn kind(&self, path: &Path) -> Option<EntryKind> {
+        match self.index.get(path)? {
+            Some(_) => Some(EntryKind::Data),
+            None => Some(EntryKind::Container),
+        }
+    }
+
+    fn remove(&self, path: &Path) -> Result<(), io::Error> {
+        Err(read_only(path))
+    }
+
+    fn create_dir(&self, path: &Path) -> Result<(), io::Error> {
+        Err(read_only(path))
+    }
+
+    fn rename(&self, from: &Path, _to: &Path) -> Result<(), io::Error> {
+        Err(read_only(from))
+    }
+}
@@ -0,0 +1,153 @@
+use super::*;
+use std::collections::BTreeMap;
+use std::io::Cursor;
+use std::ops::Bound;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// An entry of a `MemoryBackend` (`None` is a directory)
+type Node = Option<Arc<[u8]>>;
+
+/// An in-memory `StorageBackend` for tests and short-lived caches
+/// 
+/// Clones share the same storage; everything is lost once the last clone is dropped
+#[derive(Default, Clone)]
+pub struct MemoryBackend {
+    nodes: Arc<Mutex<BTreeMap<PathBuf, Node>>>,
+}
+
+impl MemoryBackend {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lists the paths of everything within a directory (at any depth)
+    fn descendants(nodes: &BTreeMap<PathBuf, Node>, path: &Path) -> Vec<PathBuf> {
+        nodes.range::<Path, _>((Bound::Excluded(path), Bound::Unbounded))
+            .take_while(|(x, _)| x.starts_with(path))
+            .map(|(x, _)| x.clone())
+            .collect()
+    }
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("'{}' not found", path.to_string_lossy()))
+}
+
+/// Checks that the parent directory of a path exists
+fn check_parent(nodes: &BTreeMap<PathBuf, Node>, path: &Path) -> Result<(), io::Error> {
+    match path.parent() {
+        Some(x) if !matches!(nodes.get(x), Some(None)) => Err(not_found(x)),
+        _ => Ok(()),
+    }
+}
+
+/// A writer that buffers everything written and stores it in a `MemoryBackend` once committed
+struct MemoryWriter {
+    nodes: Arc<Mutex<BTreeMap<PathBuf, Node>>>,
+    path: PathBuf,
+    buffer: Vec<u8>,
+}
+
+impl Write for MemoryWriter {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> Result<usize, io::Error> {
+        self.buffer.write(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<(), io::Error> {
+        Ok(())
+    }
+}
+
+impl StorageWriter for MemoryWriter {
+    fn commit(self: Box<Self>) -> Result<(), io::Error> {
+        let mut nodes = self.nodes.lock().unwrap();
+        check_parent(&nodes, &self.path)?;
+        if let Some(None) = nodes.get(&self.path) {
+            return Err(io::Error::other(format!("'{}' is a directory", self.path.to_string_lossy())));
+        }
+        nodes.insert(self.path, Some(self.buffer.into()));
+        Ok(())
+    }
+}
+
+impl StorageBackend for MemoryBackend {
+    fn open_reader(&self, path: &Path) -> Result<FileWrapper, io::Error> {
+        match self.nodes.lock().unwrap().get(path) {
+            Some(Some(data)) => Ok(FileWrapper::from_reader(Cursor::new(data.clone()))),
+            _ => Err(not_found(path)),
+        }
+    }
+
+    fn open_writer(&self, path: &Path) -> Result<FileWrapper, io::Error> {
+        check_parent(&self.nodes.lock().unwrap(), path)?;
+        Ok(FileWrapper::from_writer(MemoryWriter {
+            nodes: self.nodes.clone(),
+            path: path.to_path_buf(),
+            buffer: Vec::new(),
+        }))
+    }
+
+    fn list(&self, path: &Path) -> Result<Vec<(String, EntryKind)>, io::Error> {
+        let nodes = self.nodes.lock().unwrap();
+        if !matches!(nodes.get(path), Some(None)) { return Err(not_found(path)) };
+        Ok(nodes.range::<Path, _>((Bound::Excluded(path), Bound::Unbounded))
+            .take_while(|(x, _)| x.starts_with(path))
+            .filter(|(x, _)| x.parent() == Some(path))
+            .map(|(x, node)| {
+                let name = x.file_name().unwrap_or_default().to_string_lossy().to_string();
+                (name, if node.is_some() { EntryKind::Data } else { EntryKind::Container })
+            }).collect())
+    }
+
+    fn kind(&self, path: &Path) -> Option<EntryKind> {
+        match self.nodes.lock().unwrap().get(path)? {
+            Some(_) => Some(EntryKind::Data),
+            None => Some(EntryKind::Container),
+        }
+    }
+
+    fn remove(&self, path: &Path) -> Result<(), io::Error> {
+        let mut nodes = self.nodes.lock().unwrap();
+        if nodes.remove(path).is_none() { return Err(not_found(path)) };
+        for x in Self::descendants(&nodes, path) { nodes.remove(&x); };
+        Ok(())
+    }
+
+    fn create_dir(&self, path: &Path) -> Result<(), io::Error> {
+        let mut nodes = self.nodes.lock().unwrap();
+        for dir in path.ancestors() {
+            match nodes.get(dir) {
+                Some(None) => break,
+                Some(Some(_)) => return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("'{}' is not a directory", dir.to_string_lossy()))),
+                None => { nodes.insert(dir.to_path_buf(), None); },
+            }
+        }
+        Ok(())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), io::Error> {
+        let mut nodes = self.nodes.lock().unwrap();
+        check_parent(&nodes, to)?;
+        let node = match nodes.remove(from) {
+            Some(x) => x,
+            None => return Err(not_found(from)),
+        };
+
+        // Replace whatever was at the destination
+        if nodes.remove(to).is_some() {
+            for x in Self::descendants(&nodes, to) { nodes.remove(&x); };
+        }
+
+        // Move the entry and everything within it
+        for x in Self::descendants(&nodes, from) {
+            let child = nodes.remove(&x).unwrap();
+            nodes.insert(to.join(x.strip_prefix(from).unwrap()), child);
+        }
+        nodes.insert(to.to_path_buf(), node);
+        Ok(())
+    }
+}
@@ -1,17 +1,6 @@
 use crate::*;
-
-#[macro_export]
-macro_rules! cache_field {
-    ($name:ident($this:ident, $logger:ident) -> $type:ty $code:block) => {
-        #[allow(unused_mut)]
-        pub fn $name(&mut self, mut $logger: impl Logger) -> &mut $type {
-            let $this = self;
-            if $this.$name.is_none() {
-                $this.$name = Some($code);
-            }; $this.$name.as_mut().unwrap()
-        }
-    }
-}
+use std::cell::RefCell;
+use std::collections::HashSet;
 
 #[allow(drop_bounds)]
 pub trait LazyObject: Drop {
@@ -19,4 +8,30 @@ pub trait LazyObject: Drop {
     fn store_lazy(&self) -> Result<(), LDBError>;
     fn load_lazy(container: LazyContainer) -> Self;
     fn clear_cache(&mut self);
-}
\ No newline at end of file
+}
+
+/// Keeps track of which fields of a `LazyObject` have been modified since they were last stored
+///
+/// Used by `#[derive(LazyObject)]` so that `store_lazy` only writes the fields that have actually changed
+#[derive(Debug, Default)]
+pub struct DirtyFields(RefCell<HashSet<&'static str>>);
+
+impl DirtyFields {
+    /// Marks a field as modified
+    #[inline]
+    pub fn mark(&self, field: &'static str) {
+        self.0.borrow_mut().insert(field);
+    }
+
+    /// Checks if a field has been modified since it was last stored
+    #[inline]
+    pub fn is_dirty(&self, field: &str) -> bool {
+        self.0.borrow().contains(field)
+    }
+
+    /// Marks all of the fields as stored
+    #[inline]
+    pub fn clear(&self) {
+        self.0.borrow_mut().clear();
+    }
+}
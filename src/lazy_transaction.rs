@@ -0,0 +1,280 @@
+use crate::*;
+use crate::lazy_link::Links;
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::io;
+
+/// The name of the directory (within the database) that changes are staged in
+pub(crate) const STAGING_DIR: &str = ".txn";
+/// The name of the directory (within the database) that a committed transaction which couldn't be applied is moved to
+pub(crate) const FAILED_DIR: &str = ".txn-failed";
+/// The name of the file that lists the changes of a committed transaction
+const JOURNAL: &str = "journal";
+/// The name of the file that records how many changes of the journal have been applied
+const PROGRESS: &str = "progress";
+
+/// A staged change of a `Transaction` (paths are relative to the database root)
+enum Change {
+    Write { staged: String, target: PathBuf },
+    Remove { target: PathBuf },
+    CreateDir { target: PathBuf },
+}
+
+impl Change {
+    /// Appends the change's fields to the journal (a string array, so keys can hold any characters)
+    fn encode(&self, fields: &mut Vec<String>) -> Result<(), LDBError> {
+        let path = |x: &Path| match x.to_str() {
+            Some(x) => Ok(x.to_string()),
+            None => Err(LDBError::InvalidUTF8String(x.as_os_str().as_encoded_bytes().into())),
+        };
+        match self {
+            Change::Write { staged, target } => fields.extend([String::from("W"), staged.clone(), path(target)?]),
+            Change::Remove { target } => fields.extend([String::from("R"), path(target)?]),
+            Change::CreateDir { target } => fields.extend([String::from("D"), path(target)?]),
+        }; Ok(())
+    }
+
+    /// Decodes the changes of a journal
+    fn decode(fields: Vec<String>) -> Result<Vec<Self>, LDBError> {
+        let invalid = || LDBError::InvalidValue(LazyType::Array, String::from("malformed transaction journal"));
+        let mut fields = fields.into_iter();
+        let mut changes = Vec::new();
+        while let Some(kind) = fields.next() {
+            let mut next = || fields.next().ok_or_else(invalid);
+            changes.push(match kind.as_str() {
+                "W" => Change::Write { staged: next()?, target: PathBuf::from(next()?) },
+                "R" => Change::Remove { target: PathBuf::from(next()?) },
+                "D" => Change::CreateDir { target: PathBuf::from(next()?) },
+                _ => return Err(invalid()),
+            });
+        }
+        Ok(changes)
+    }
+
+    /// Applies the change to the database (applying the same change twice has no extra effect)
+    fn apply(&self, backend: &dyn StorageBackend, root: &Path, staging: &Path) -> Result<(), io::Error> {
+        match self {
+            Change::Write { staged, target } => {
+                let staged = staging.join(staged);
+                if backend.kind(&staged).is_none() { return Ok(()) }; // already applied
+                let target = root.join(target);
+                if let Some(x) = target.parent() { backend.create_dir(x)? };
+                backend.rename(&staged, &target)
+            },
+            Change::Remove { target } => {
+                let target = root.join(target);
+                if backend.kind(&target).is_some() { backend.remove(&target) }
+                else { Ok(()) }
+            },
+            Change::CreateDir { target } => backend.create_dir(&root.join(target)),
+        }
+    }
+}
+
+/// The staged changes shared by a `Transaction` and it's child containers
+struct Staging {
+    backend: Arc<dyn StorageBackend>,
+    dir: PathBuf,
+    changes: Vec<Change>,
+    committed: bool,
+}
+
+impl Drop for Staging {
+    fn drop(&mut self) {
+        // Discard the staged changes unless they've been committed (then recovery is left to `LazyDB::load_dir`)
+        if !self.committed { let _ = self.backend.remove(&self.dir); }
+    }
+}
+
+/// A set of changes to a `LazyDB` that are applied all together or not at all
+///
+/// Changes are staged in a hidden directory of the database and only applied once the transaction commits;
+/// reads within the transaction see the staged changes
+pub struct Transaction<'a> {
+    staging: &'a RefCell<Staging>,
+    root: &'a Path,
+    prefix: PathBuf,
+}
+
+impl<'a> Transaction<'a> {
+    /// Generates a `FileWrapper` in write mode for a key of this transaction's container
+    ///
+    /// The data is staged and only replaces the existing data when the transaction commits
+    pub fn data_writer(&self, key: impl AsRef<Path>) -> Result<FileWrapper, LDBError> {
+        let mut staging = self.staging.borrow_mut();
+        let staged = staging.changes.len().to_string();
+        let file = unwrap_result!((staging.backend.open_writer(&staging.dir.join(&staged))) err => LDBError::IOError(err));
+        staging.changes.push(Change::Write { staged, target: self.prefix.join(key) });
+        Ok(file)
+    }
+
+    /// Writes any value that implements `ToLazy` as `LazyData` at the specified key when the transaction commits
+    #[inline]
+    pub fn write<T: ToLazy>(&self, key: impl AsRef<Path>, value: T) -> Result<(), LDBError> {
+        value.to_lazy(self.data_writer(key)?)
+    }
+
+    /// Reads `LazyData` at the specified key (including changes staged by this transaction)
+    pub fn read_data(&self, key: impl AsRef<Path>) -> Result<LazyData, LDBError> {
+        let target = self.prefix.join(key);
+        let staging = self.staging.borrow();
+
+        // Find the latest staged change to the key
+        for change in staging.changes.iter().rev() {
+            match change {
+                Change::Write { staged, target: x } if *x == target => return LazyData::load_from(&*staging.backend, staging.dir.join(staged)),
+                Change::Remove { target: x } if target.starts_with(x) => return Err(LDBError::FileNotFound(self.root.join(target))),
+                _ => (),
+            }
+        }
+
+        LazyData::load_from(&*staging.backend, self.root.join(target))
+    }
+
+    /// Gets a nested container within this transaction's container (created when the transaction commits if it doesn't exist)
+    pub fn child_container(&self, key: impl AsRef<Path>) -> Result<Transaction<'a>, LDBError> {
+        let target = self.prefix.join(key);
+        self.staging.borrow_mut().changes.push(Change::CreateDir { target: target.clone() });
+        Ok(Transaction {
+            staging: self.staging,
+            root: self.root,
+            prefix: target,
+        })
+    }
+
+    /// Removes the `LazyData` or container at the specified key when the transaction commits
+    pub fn remove(&self, key: impl AsRef<Path>) -> Result<(), LDBError> {
+        self.staging.borrow_mut().changes.push(Change::Remove { target: self.prefix.join(key) });
+        Ok(())
+    }
+}
+
+impl LazyDB {
+    /// Runs a closure within a `Transaction` and commits it's changes if the closure returns `Ok`
+    ///
+    /// If the closure returns an error or panics, none of it's changes are applied.
+    /// If the process crashes while committing, the commit is finished by the next `LazyDB::load_dir`
+    pub fn transaction<R, F>(&mut self, f: F) -> Result<R, LDBError>
+    where F: FnOnce(&Transaction) -> Result<R, LDBError> {
+        if self.is_read_only() { return Err(LDBError::ReadOnly(self.path().to_path_buf())) };
+        let backend = self.backend();
+        let dir = self.path().join(STAGING_DIR);
+        if backend.kind(&dir).is_some() { unwrap_result!((backend.remove(&dir)) err => LDBError::IOError(err)) };
+        unwrap_result!((backend.create_dir(&dir)) err => LDBError::IOError(err));
+
+        let staging = RefCell::new(Staging { backend, dir, changes: Vec::new(), committed: false });
+        let result = f(&Transaction {
+            staging: &staging,
+            root: self.path(),
+            prefix: PathBuf::new(),
+        })?;
+
+        commit(self.path(), &self.links, &mut staging.borrow_mut())?;
+        Ok(result)
+    }
+}
+
+/// Writes the journal (the commit point) and then applies the staged changes
+/// 
+/// Every staged value is already durable, as the backend's writers only commit once they've been fully written
+fn commit(root: &Path, links: &Links, staging: &mut Staging) -> Result<(), LDBError> {
+    let backend = &*staging.backend;
+
+    // Check the changes can be applied (so a commit doesn't fail half way through)
+    check(backend, root, &staging.changes)?;
+
+    // Find the staged links, so they're indexed before the journal can apply them
+    let mut staged_links = Vec::new();
+    for change in staging.changes.iter() {
+        let Change::Write { staged, target } = change else { continue };
+        let Ok(data) = LazyData::load_from(backend, staging.dir.join(staged)) else { continue }; // never finished
+        if data.lazy_type == LazyType::Link { staged_links.push((root.join(target), data.collect_link_path()?)) };
+    }
+
+    links.record(backend, &staged_links, || {
+        // Write the journal
+        let mut journal = Vec::new();
+        for change in staging.changes.iter() { change.encode(&mut journal)? };
+        let writer = unwrap_result!((backend.open_writer(&staging.dir.join(JOURNAL))) err => LDBError::IOError(err));
+        LazyData::new_string_array(writer, &journal)?;
+        staging.committed = true;
+
+        replay(backend, root, &staging.dir, &staging.changes, 0)
+    })
+}
+
+/// Checks that no change would replace a container with data (or data with a container) that isn't removed first
+fn check(backend: &dyn StorageBackend, root: &Path, changes: &[Change]) -> Result<(), LDBError> {
+    for (i, change) in changes.iter().enumerate() {
+        let (target, kind) = match change {
+            Change::Write { target, .. } => (target, EntryKind::Container),
+            Change::CreateDir { target } => (target, EntryKind::Data),
+            Change::Remove { .. } => continue,
+        };
+        let removed = changes[..i].iter().any(|x| matches!(x, Change::Remove { target: x } if target.starts_with(x)));
+        let conflict = target.ancestors().filter(|x| *x != Path::new("")).any(|x| {
+            let expected = if x == target { kind } else { EntryKind::Data }; // parents can't be data either
+            backend.kind(&root.join(x)) == Some(expected)
+        });
+        if conflict && !removed {
+            let err = std::io::Error::new(std::io::ErrorKind::AlreadyExists, format!("'{}' is in the way of the transaction", target.to_string_lossy()));
+            return Err(LDBError::IOError(err));
+        }
+    }
+    Ok(())
+}
+
+/// Applies the changes of a journal (see `apply`), moving the staging directory aside to `.txn-failed` if they can't be applied
+/// so that the database can still be loaded
+fn replay(backend: &dyn StorageBackend, root: &Path, dir: &Path, changes: &[Change], start: usize) -> Result<(), LDBError> {
+    let result = apply(backend, root, dir, changes, start);
+    if result.is_err() { quarantine(backend, root, dir) };
+    result
+}
+
+/// Moves a staging directory that can't be applied aside (replacing any older one) so that it's never replayed
+fn quarantine(backend: &dyn StorageBackend, root: &Path, dir: &Path) {
+    let failed = root.join(FAILED_DIR);
+    if backend.kind(&failed).is_some() { let _ = backend.remove(&failed); };
+    let _ = backend.rename(dir, &failed);
+}
+
+/// Applies the changes of a journal, starting from a specific change, and then removes the staging directory
+fn apply(backend: &dyn StorageBackend, root: &Path, dir: &Path, changes: &[Change], start: usize) -> Result<(), LDBError> {
+    for (i, change) in changes.iter().enumerate().skip(start) {
+        unwrap_result!((change.apply(backend, root, dir)) err => LDBError::IOError(err));
+        let writer = unwrap_result!((backend.open_writer(&dir.join(PROGRESS))) err => LDBError::IOError(err));
+        LazyData::new_u64(writer, i as u64 + 1)?;
+    }
+
+    // Remove the journal first so that a partially removed staging directory is never replayed
+    unwrap_result!((backend.remove(&dir.join(JOURNAL))) err => LDBError::IOError(err));
+    unwrap_result!((backend.remove(dir)) err => LDBError::IOError(err));
+    Ok(())
+}
+
+/// Finishes a committed transaction that was interrupted or discards an uncommitted one
+pub(crate) fn recover(backend: &dyn StorageBackend, root: &Path) -> Result<(), LDBError> {
+    let dir = root.join(STAGING_DIR);
+    if backend.kind(&dir) != Some(EntryKind::Container) { return Ok(()) };
+
+    // Never committed; discard it
+    let journal = dir.join(JOURNAL);
+    if backend.kind(&journal) != Some(EntryKind::Data) {
+        unwrap_result!((backend.remove(&dir)) err => LDBError::IOError(err));
+        return Ok(());
+    }
+
+    // Committed; finish applying it
+    let changes = LazyData::load_from(backend, &journal)
+        .and_then(|x| x.collect_string_array())
+        .and_then(|x| Change::decode(x.into_vec()));
+    let changes = match changes {
+        Ok(x) => x,
+        Err(e) => { quarantine(backend, root, &dir); return Err(e) },
+    };
+    let progress = dir.join(PROGRESS);
+    let start = if backend.kind(&progress).is_some() { LazyData::load_from(backend, progress)?.collect_u64()? as usize } else { 0 };
+    replay(backend, root, &dir, &changes, start)
+}
@@ -1,4 +1,5 @@
 mod converter;
+#[allow(unused_imports)] // `converter` only has trait impls for now
 pub use converter::*;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -23,4 +24,13 @@ pub enum LazyType {
     False,
     Link,
     Array,
+    Timestamp,
+    Duration,
+    Uuid,
+    Char,
+    Decimal,
+    Option,
+    Enum,
+    Map,
+    Tensor,
 }
\ No newline at end of file
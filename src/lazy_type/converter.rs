@@ -28,6 +28,15 @@ impl TryFrom<u8> for LazyType {
             16 => False,
             17 => Link,
             18 => Array,
+            19 => Timestamp,
+            20 => Duration,
+            21 => Uuid,
+            22 => Char,
+            23 => Decimal,
+            24 => Option,
+            25 => Enum,
+            26 => Map,
+            27 => Tensor,
             _ => return Err(LDBError::InvalidLazyType(byte)),
         })
     }
@@ -56,6 +65,15 @@ impl From<LazyType> for u8 {
             False => 16,
             Link => 17,
             Array => 18,
+            Timestamp => 19,
+            Duration => 20,
+            Uuid => 21,
+            Char => 22,
+            Decimal => 23,
+            Option => 24,
+            Enum => 25,
+            Map => 26,
+            Tensor => 27,
         }
     }
 }
\ No newline at end of file
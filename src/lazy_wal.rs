@@ -0,0 +1,246 @@
+use crate::*;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::collections::HashSet;
+
+/// The name of the write-ahead log file (next to `.meta`)
+pub(crate) const WAL_FILE: &str = ".wal";
+
+/// An append-only log of every mutation made through the `LazyContainer`s of a `LazyDB`
+///
+/// Each mutation is logged (and synced) before it's applied, so that incomplete mutations can be finished or discarded after a crash.
+///
+/// ## Records
+/// Each record is it's length (`u32`) followed by it's fields, each of which is also prefixed with it's length (`u32`), all big-endian
+/// (so paths can hold any characters); paths are relative to the database root
+/// - `B <id> W <tmp> <target>`: a value is about to be written to `tmp` and renamed to `target`
+/// - `B <id> R <target>`: `target` is about to be removed
+/// - `B <id> N <tmp> <old> <target>`: `target` is about to be replaced with a new, empty container
+/// - `B <id> X <target>`: `target` is about to be wiped
+/// - `B <id> S <target> <offset> <bytes>`: `bytes` are about to be written to `target` in place (redone if interrupted)
+/// - `B <id> T <target> <len>`: `target` is about to be truncated in place (redone if interrupted)
+/// - `C <id>`: the value of a write has been fully written and synced
+/// - `E <id>`: the mutation has been fully applied (or aborted)
+pub(crate) struct Wal {
+    root: PathBuf,
+    state: Mutex<WalState>,
+}
+
+struct WalState {
+    file: File,
+    next_id: u64,
+    in_flight: usize,
+}
+
+impl Wal {
+    /// Opens the write-ahead log of a database (creating it if it doesn't exist)
+    pub(crate) fn open(root: &Path) -> Result<Arc<Self>, io::Error> {
+        let file = OpenOptions::new().create(true).append(true).open(root.join(WAL_FILE))?;
+        Ok(Arc::new(Self {
+            root: root.to_path_buf(),
+            state: Mutex::new(WalState { file, next_id: 0, in_flight: 0 }),
+        }))
+    }
+
+    /// Checks if a database has a write-ahead log
+    #[inline]
+    pub(crate) fn exists(root: &Path) -> bool {
+        root.join(WAL_FILE).is_file()
+    }
+
+    fn relative(&self, path: &Path) -> String {
+        path.strip_prefix(&self.root).unwrap_or(path).to_string_lossy().to_string()
+    }
+
+    /// Appends a record (written all at once) and syncs it to disk
+    fn append(state: &mut WalState, fields: &[&[u8]]) -> Result<(), io::Error> {
+        let mut record = Vec::new();
+        for field in fields {
+            record.extend_from_slice(&(field.len() as u32).to_be_bytes());
+            record.extend_from_slice(field);
+        }
+        let mut bytes = (record.len() as u32).to_be_bytes().to_vec();
+        bytes.append(&mut record);
+        state.file.write_all(&bytes)?;
+        state.file.sync_data()
+    }
+
+    /// Logs the beginning of a mutation (with any extra fields after it's paths) and returns it's id
+    fn begin(&self, kind: &str, paths: &[&Path], extra: &[&[u8]]) -> Result<u64, io::Error> {
+        let mut state = self.state.lock().unwrap();
+        let id = state.next_id;
+        let (id_field, paths) = (id.to_string(), paths.iter().map(|x| self.relative(x)).collect::<Vec<_>>());
+        let fields: Vec<&[u8]> = [b"B", id_field.as_bytes(), kind.as_bytes()].into_iter()
+            .chain(paths.iter().map(|x| x.as_bytes()))
+            .chain(extra.iter().copied())
+            .collect();
+        Self::append(&mut state, &fields)?;
+        state.next_id += 1;
+        state.in_flight += 1;
+        Ok(id)
+    }
+
+    /// Logs that a write has been fully written and synced
+    pub(crate) fn commit(&self, id: u64) -> Result<(), io::Error> {
+        Self::append(&mut self.state.lock().unwrap(), &[b"C", id.to_string().as_bytes()])
+    }
+
+    /// Logs the end of a mutation
+    pub(crate) fn end(&self, id: u64) -> Result<(), io::Error> {
+        let mut state = self.state.lock().unwrap();
+        Self::append(&mut state, &[b"E", id.to_string().as_bytes()])?;
+        state.in_flight -= 1;
+        Ok(())
+    }
+
+    #[inline]
+    pub(crate) fn begin_write(&self, tmp: &Path, target: &Path) -> Result<u64, io::Error> {
+        self.begin("W", &[tmp, target], &[])
+    }
+
+    #[inline]
+    pub(crate) fn begin_remove(&self, target: &Path) -> Result<u64, io::Error> {
+        self.begin("R", &[target], &[])
+    }
+
+    #[inline]
+    pub(crate) fn begin_new_container(&self, tmp: &Path, old: &Path, target: &Path) -> Result<u64, io::Error> {
+        self.begin("N", &[tmp, old, target], &[])
+    }
+
+    #[inline]
+    pub(crate) fn begin_wipe(&self, target: &Path) -> Result<u64, io::Error> {
+        self.begin("X", &[target], &[])
+    }
+
+    #[inline]
+    pub(crate) fn begin_write_at(&self, target: &Path, offset: u64, bytes: &[u8]) -> Result<u64, io::Error> {
+        self.begin("S", &[target], &[offset.to_string().as_bytes(), bytes])
+    }
+
+    #[inline]
+    pub(crate) fn begin_truncate(&self, target: &Path, len: u64) -> Result<u64, io::Error> {
+        self.begin("T", &[target], &[len.to_string().as_bytes()])
+    }
+
+    /// Empties the log if there are no mutations in progress
+    pub(crate) fn checkpoint(&self) -> Result<(), io::Error> {
+        let state = self.state.lock().unwrap();
+        if state.in_flight == 0 { state.file.set_len(0)?; state.file.sync_data()? };
+        Ok(())
+    }
+}
+
+impl Drop for Wal {
+    fn drop(&mut self) {
+        let _ = self.checkpoint();
+    }
+}
+
+/// Finishes or discards the incomplete mutations of a database's write-ahead log and then empties it
+pub(crate) fn recover(root: &Path) -> Result<(), LDBError> {
+    let path = root.join(WAL_FILE);
+    if !path.is_file() { return Ok(()) };
+
+    let mut log = Vec::new();
+    let mut file = unwrap_result!((File::open(&path)) err => LDBError::IOError(err));
+    unwrap_result!((file.read_to_end(&mut log)) err => LDBError::IOError(err));
+
+    // Collect the mutations that never ended (in order)
+    let mut begun: Vec<(&[u8], Vec<&[u8]>)> = Vec::new();
+    let mut committed = HashSet::new();
+    let mut log = log.as_slice();
+    while let Some(record) = take(&mut log) { // stops at a torn record from a crash
+        let Some(fields) = decode(record) else { break };
+        match fields.as_slice() {
+            [b"B", id, record @ ..] => begun.push((id, record.to_vec())),
+            [b"C", id] => { committed.insert(*id); },
+            [b"E", id] => begun.retain(|(x, _)| x != id),
+            _ => (),
+        }
+    }
+
+    for (id, record) in begun {
+        // Every field is text apart from the bytes of an in-place write (it's last field)
+        let (record, bytes) = match record.split_last() {
+            Some((bytes, fields)) if fields.first() == Some(&&b"S"[..]) => (fields, *bytes),
+            _ => (record.as_slice(), &[][..]),
+        };
+        let Ok(text) = record.iter().map(|x| std::str::from_utf8(x)).collect::<Result<Vec<_>, _>>() else { continue };
+        let result = match text.as_slice() {
+            ["W", tmp, target] => {
+                let (tmp, target) = (root.join(tmp), root.join(target));
+                if committed.contains(id) && tmp.is_file() { fs::rename(tmp, target) } // redo
+                else if tmp.is_file() { fs::remove_file(tmp) } // discard
+                else { Ok(()) }
+            },
+            ["R", target] => remove_any(&root.join(target)),
+            ["N", tmp, old, target] => {
+                let target = root.join(target);
+                remove_any(&root.join(tmp))
+                    .and_then(|_| remove_any(&root.join(old)))
+                    .and_then(|_| remove_any(&target))
+                    .and_then(|_| fs::create_dir_all(&target))
+            },
+            ["X", target] => {
+                let target = root.join(target);
+                remove_any(&target).and_then(|_| fs::create_dir_all(&target))
+            },
+            ["S", target, offset] => match offset.parse() {
+                Ok(offset) if root.join(target).is_file() => write_at(&root.join(target), offset, bytes), // redo
+                _ => Ok(()),
+            },
+            ["T", target, len] => match len.parse() {
+                Ok(len) if root.join(target).is_file() => truncate(&root.join(target), len), // redo
+                _ => Ok(()),
+            },
+            _ => Ok(()),
+        };
+        unwrap_result!((result) err => LDBError::IOError(err));
+    }
+
+    // Empty the log
+    unwrap_result!((file.sync_all()) err => LDBError::IOError(err));
+    let file = unwrap_result!((OpenOptions::new().write(true).truncate(true).open(&path)) err => LDBError::IOError(err));
+    unwrap_result!((file.sync_all()) err => LDBError::IOError(err));
+    Ok(())
+}
+
+/// Writes bytes to a file in place from an offset and syncs it
+pub(crate) fn write_at(path: &Path, offset: u64, bytes: &[u8]) -> Result<(), io::Error> {
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+    file.write_all(bytes)?;
+    file.sync_data()
+}
+
+/// Shortens a file in place to a length and syncs it (doing nothing if it's already shorter)
+pub(crate) fn truncate(path: &Path, len: u64) -> Result<(), io::Error> {
+    let file = OpenOptions::new().write(true).open(path)?;
+    if file.metadata()?.len() > len { file.set_len(len)? };
+    file.sync_data()
+}
+
+/// Takes a length-prefixed slice from the front of the bytes, if they're long enough
+fn take<'a>(bytes: &mut &'a [u8]) -> Option<&'a [u8]> {
+    let len = u32::from_be_bytes(bytes.get(..4)?.try_into().unwrap()) as usize;
+    let taken = bytes.get(4..4 + len)?;
+    *bytes = &bytes[4 + len..];
+    Some(taken)
+}
+
+/// Decodes the fields of a record
+fn decode(mut record: &[u8]) -> Option<Vec<&[u8]>> {
+    let mut fields = Vec::new();
+    while !record.is_empty() { fields.push(take(&mut record)?) };
+    Some(fields)
+}
+
+/// Removes a file or directory if it exists
+pub(crate) fn remove_any(path: &Path) -> Result<(), io::Error> {
+    if path.is_dir() { fs::remove_dir_all(path) }
+    else if path.is_file() { fs::remove_file(path) }
+    else { Ok(()) }
+}
@@ -0,0 +1,130 @@
+use crate::*;
+use std::collections::VecDeque;
+use std::fmt;
+
+/// The order in which `walk` visits the tree of a `LazyContainer`
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum WalkOrder {
+    /// Visits a container's contents before moving on to it's siblings
+    DepthFirst,
+    /// Visits every entry of a depth before moving on to the next depth
+    BreadthFirst,
+}
+
+/// Returned by the visitor of `walk` to control how the walk continues
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum WalkControl {
+    /// Keep walking (into the contents of the visited container)
+    Continue,
+    /// Skip the contents of the visited container (same as `Continue` for `LazyData`)
+    Prune,
+    /// Stop the walk entirely
+    Stop,
+}
+
+/// The kind of entry visited by `walk`
+pub enum WalkKind {
+    Container(LazyContainer),
+    Data(LazyType),
+}
+
+/// An entry (`LazyContainer` or `LazyData`) visited by `walk`
+pub struct WalkEntry {
+    /// The names of the containers leading to this entry (including itself if it's a container)
+    pub containers: Vec<String>,
+    /// The key of the `LazyData` (`None` if the entry is a container)
+    pub key: Option<String>,
+    pub kind: WalkKind,
+}
+
+impl WalkEntry {
+    /// How many containers deep the entry is (`0` for entries at the root)
+    #[inline]
+    pub fn depth(&self) -> usize {
+        match self.key {
+            Some(_) => self.containers.len(),
+            None => self.containers.len() - 1,
+        }
+    }
+
+    #[inline]
+    pub fn is_container(&self) -> bool {
+        matches!(self.kind, WalkKind::Container(_))
+    }
+}
+
+/// Formats the entry's path the same way as `search_database!` (`/a/b` for containers and `/a/b::key` for data)
+impl fmt::Display for WalkEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "/{}", self.containers.join("/"))?;
+        if let Some(key) = &self.key { write!(f, "::{key}")? };
+        Ok(())
+    }
+}
+
+impl LazyContainer {
+    /// Visits every `LazyContainer` and `LazyData` nested within this container (not including itself)
+    /// 
+    /// Entries of the same container are visited in alphabetical order; the visitor can prune containers or stop the walk
+    pub fn walk<F>(&self, order: WalkOrder, mut visitor: F) -> Result<(), LDBError>
+    where F: FnMut(&WalkEntry) -> Result<WalkControl, LDBError> {
+        match order {
+            WalkOrder::DepthFirst => { walk_depth(self, &[], &mut visitor)?; },
+            WalkOrder::BreadthFirst => walk_breadth(self, &mut visitor)?,
+        }; Ok(())
+    }
+}
+
+impl LazyDB {
+    /// Visits every `LazyContainer` and `LazyData` within the database
+    /// 
+    /// Entries of the same container are visited in alphabetical order; the visitor can prune containers or stop the walk
+    #[inline]
+    pub fn walk<F>(&self, order: WalkOrder, visitor: F) -> Result<(), LDBError>
+    where F: FnMut(&WalkEntry) -> Result<WalkControl, LDBError> {
+        self.as_container()?.walk(order, visitor)
+    }
+}
+
+/// Loads an entry of a container
+fn load_entry(container: &LazyContainer, parents: &[String], key: String, is_dir: bool) -> Result<WalkEntry, LDBError> {
+    Ok(if is_dir {
+        let child = container.read_container(&key)?;
+        let mut containers = parents.to_vec();
+        containers.push(key);
+        WalkEntry { containers, key: None, kind: WalkKind::Container(child) }
+    } else {
+        let lazy_type = container.read_data(&key)?.lazy_type;
+        WalkEntry { containers: parents.to_vec(), key: Some(key), kind: WalkKind::Data(lazy_type) }
+    })
+}
+
+/// Returns `true` if the walk was stopped
+fn walk_depth<F>(container: &LazyContainer, parents: &[String], visitor: &mut F) -> Result<bool, LDBError>
+where F: FnMut(&WalkEntry) -> Result<WalkControl, LDBError> {
+    for (key, is_dir) in container.entries()? {
+        let entry = load_entry(container, parents, key, is_dir)?;
+        match (visitor(&entry)?, entry.kind) {
+            (WalkControl::Stop, _) => return Ok(true),
+            (WalkControl::Continue, WalkKind::Container(child)) if walk_depth(&child, &entry.containers, visitor)? => return Ok(true),
+            _ => (),
+        }
+    }
+    Ok(false)
+}
+
+fn walk_breadth<F>(container: &LazyContainer, visitor: &mut F) -> Result<(), LDBError>
+where F: FnMut(&WalkEntry) -> Result<WalkControl, LDBError> {
+    let mut queue = VecDeque::from([(Vec::new(), container.clone())]);
+    while let Some((parents, container)) = queue.pop_front() {
+        for (key, is_dir) in container.entries()? {
+            let entry = load_entry(&container, &parents, key, is_dir)?;
+            match (visitor(&entry)?, entry.kind) {
+                (WalkControl::Stop, _) => return Ok(()),
+                (WalkControl::Continue, WalkKind::Container(child)) => queue.push_back((entry.containers, child)),
+                _ => (),
+            }
+        }
+    }
+    Ok(())
+}
@@ -11,6 +11,14 @@
 //!     - A collection of `LazyData`, think of it like an object from `OOP` or a directory in a file system
 //!     - An abstaction of the underlying filesystem directory
 //! 
+//! ## Cargo Features
+//! - `derive`: `#[derive(LazyObject)]` for generating `LazyObject` implementations
+//! - `serde`: the `lazy_serde` module for storing any `Serialize` type in a `LazyContainer`
+//! - `mmap`: `LazyData::map_binary` and `LazyData::map_array` for reading large binary and array data without copying it into memory (`unsafe`, as the files mustn't be modified in place while mapped)
+//! - `chrono`: conversions between `LazyTimestamp` (`LazyType::Timestamp`) and `chrono::DateTime<Utc>`
+//! - `uuid`: conversions between `LazyUuid` (`LazyType::Uuid`) and `uuid::Uuid`
+//! - `rust_decimal`: conversions between `LazyDecimal` (`LazyType::Decimal`) and `rust_decimal::Decimal`
+//! 
 //! ## Examples
 //! ### Some basic usage
 //! Here is a really basic `LazyDB` that holds some information about a hypothetical person named *'Dave'*
@@ -35,6 +43,36 @@
 //! let age: u8 = search_database!((&database) /people/Dave::age).unwrap().collect_u8().unwrap();
 //! let unemployed: bool = search_database!((&database) /people/Dave::unemployed).unwrap().collect_bool().unwrap();
 //! ```
+//! ### Generic reading and writing
+//! Any type that implements `ToLazy` can be written without a `new_<type>` function and any type that implements `FromLazy` can be collected with `collect`
+//! ```rust
+//! use lazy_db::*;
+//! 
+//! let path = "example_generic_db"; // path to the database
+//! let database = LazyDB::init_db(path).unwrap(); // initialise the database
+//! 
+//! // Writing without specifying the type
+//! write_database!((&database) /people/Dave::fav_colour = "Blue").unwrap();
+//! write_database!((&database) /people/Dave::age = 21u8).unwrap();
+//! write_database!((&database) /people/Dave::lucky_numbers = vec![7u16, 13, 42]).unwrap();
+//! 
+//! // Reading with the type inferred
+//! let fav_colour: String = search_database!((&database) /people/Dave::fav_colour).unwrap().collect().unwrap();
+//! let age = search_database!((&database) /people/Dave::age).unwrap().collect::<u8>().unwrap();
+//! let lucky_numbers: Vec<u16> = search_database!((&database) /people/Dave::lucky_numbers).unwrap().collect().unwrap();
+//! ```
+//! ### In-memory databases
+//! A `LazyDB` can live entirely in memory (for tests and short-lived caches) and still be compiled into a byte buffer or file
+//! ```rust
+//! use lazy_db::*;
+//!
+//! let database = LazyDB::in_memory().unwrap(); // nothing touches the filesystem
+//! write_database!((&database) /people/Dave::age = 21u8).unwrap();
+//! let age: u8 = search_database!((&database) /people/Dave::age).unwrap().collect().unwrap();
+//!
+//! let mut compiled = Vec::new();
+//! database.compile_to(&mut compiled).unwrap(); // can be written to a `.ldb` file and loaded with `LazyDB::load_db`
+//! ```
 //! ### A Lazy Object
 //! An example implementation of LazyObject
 //! ```rust
@@ -56,7 +94,7 @@
 //!         };
 //!
 //!         if let Some(x) = self.age {
-//!             LazyData::new_u8(self.container.data_writer("name")?, x)?
+//!             LazyData::new_u8(self.container.data_writer("age")?, x)?
 //!         };
 //!
 //!         Ok(())
@@ -82,6 +120,33 @@
 //!     }
 //! }
 //! ```
+//! ### A Derived Lazy Object
+//! With the `derive` feature enabled, the same `LazyObject` (plus lazy getters and setters) can be generated
+//! ```rust
+//! # #[cfg(feature = "derive")]
+//! # mod example {
+//! use lazy_db::*;
+//! #[derive(LazyObject)]
+//! struct Person {
+//!     container: LazyContainer,
+//!     dirty: DirtyFields, // optional; only stores fields changed through setters
+//!     name: Option<String>,
+//!     #[lazy(rename = "years")]
+//!     age: Option<u8>,
+//! }
+//!
+//! fn birthday(container: LazyContainer) -> Result<(), LDBError> {
+//!     let mut person = Person::load_lazy(container);
+//!     let name: &String = person.name()?; // lazily loaded from the `name` key
+//!     println!("Happy birthday {name}!");
+//!     person.set_age(22); // stored at the `years` key when dropped
+//!     Ok(())
+//! }
+//! # }
+//! ```
+//!
+//! `#[lazy(no_drop)]` skips the generated `Drop` implementation, but `Drop` is a supertrait of `LazyObject`,
+//! so the struct must still implement it by hand (usually calling `store_lazy`, or doing nothing)
 
 pub mod error;
 pub mod lazy_type;
@@ -90,7 +155,15 @@ pub mod version;
 pub mod lazy_database;
 pub mod lazy_container;
 pub mod lazy_trait;
+pub mod lazy_walker;
+pub mod lazy_link;
+pub mod lazy_transaction;
+pub mod lazy_lock;
+pub mod lazy_storage;
 mod lazy_archive;
+mod lazy_wal;
+#[cfg(feature = "serde")]
+pub mod lazy_serde;
 
 // Prelude
 pub use crate::{
@@ -100,9 +173,17 @@ pub use crate::{
     lazy_database::*,
     lazy_container::*,
     lazy_trait::*,
+    lazy_walker::*,
+    lazy_link::*,
+    lazy_transaction::*,
+    lazy_lock::{LockMode, LockWait},
+    lazy_storage::*,
 };
 
-pub const VERSION: version::Version = version::Version::new(1, 2, 1);
+#[cfg(feature = "derive")]
+pub use lazy_db_derive::LazyObject;
+
+pub const VERSION: version::Version = version::Version::new(1, 4, 0);
 
 #[macro_export]
 macro_rules! const_eval {
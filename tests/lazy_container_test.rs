@@ -0,0 +1,270 @@
+mod isol;
+use isol::*;
+use lazy_db::*;
+
+#[test]
+fn lazy_container_enumerate() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("database");
+    let database = LazyDB::init(&path).unwrap();
+    let container = database.as_container().unwrap();
+
+    // Empty apart from the hidden `.meta` file
+    assert!(container.is_empty().unwrap());
+
+    write_database!((&database) /people/Dave::age = 21u8).unwrap();
+    write_database!((&database) /places::home = "Earth").unwrap();
+    write_database!((&database) count = 2u32).unwrap();
+    write_database!((&database) flag = true).unwrap();
+
+    // Keys are sorted and hidden entries are skipped
+    let keys: Vec<String> = container.keys().unwrap().collect();
+    assert_eq!(keys, ["count", "flag", "people", "places"]);
+    assert_eq!(container.len().unwrap(), 4);
+
+    // Data entries carry their `LazyType`
+    let data: Vec<(String, LazyType)> = container.data_entries().unwrap().collect();
+    assert_eq!(data, [(String::from("count"), LazyType::U32), (String::from("flag"), LazyType::True)]);
+
+    // Child containers can be read from directly
+    let children: Vec<String> = container.child_containers().unwrap().map(|(key, _)| key).collect();
+    assert_eq!(children, ["people", "places"]);
+    let (_, places) = container.child_containers().unwrap().nth(1).unwrap();
+    assert_eq!(places.read_data("home").unwrap().collect::<String>().unwrap(), "Earth");
+
+    assert!(container.contains_data("count"));
+    assert!(!container.contains_data("people"));
+    assert!(container.contains_container("people"));
+    assert!(!container.contains_container("missing"));
+}
+
+#[test]
+fn lazy_container_walk() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("database");
+    let database = LazyDB::init(&path).unwrap();
+
+    write_database!((&database) /a/b::x = 1u8).unwrap();
+    write_database!((&database) /a::y = 2u8).unwrap();
+    write_database!((&database) /c::z = 3u8).unwrap();
+    write_database!((&database) w = 4u8).unwrap();
+
+    // Depth-first
+    let mut visited = Vec::new();
+    database.walk(WalkOrder::DepthFirst, |entry| {
+        visited.push(entry.to_string());
+        Ok(WalkControl::Continue)
+    }).unwrap();
+    assert_eq!(visited, ["/a", "/a/b", "/a/b::x", "/a::y", "/c", "/c::z", "/::w"]);
+
+    // Breadth-first
+    let mut visited = Vec::new();
+    database.walk(WalkOrder::BreadthFirst, |entry| {
+        visited.push(entry.to_string());
+        Ok(WalkControl::Continue)
+    }).unwrap();
+    assert_eq!(visited, ["/a", "/c", "/::w", "/a/b", "/a::y", "/c::z", "/a/b::x"]);
+
+    // Pruning and stopping
+    let mut visited = Vec::new();
+    database.walk(WalkOrder::DepthFirst, |entry| {
+        visited.push(entry.to_string());
+        Ok(match entry.to_string().as_str() {
+            "/a" => WalkControl::Prune,
+            "/c::z" => WalkControl::Stop,
+            _ => WalkControl::Continue,
+        })
+    }).unwrap();
+    assert_eq!(visited, ["/a", "/c", "/c::z"]);
+}
+
+#[test]
+fn lazy_container_atomic_write() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("database");
+    let database = LazyDB::init(&path).unwrap();
+    let container = database.as_container().unwrap();
+    container.write("data", "old").unwrap();
+
+    // An unfinished write must leave the old value untouched
+    {
+        let mut writer = container.data_writer("data").unwrap();
+        writer.write(&[LazyType::String.into()]).unwrap();
+        writer.write(b"ne").unwrap();
+    }
+    assert_eq!(container.read_data("data").unwrap().collect::<String>().unwrap(), "old");
+
+    // A finished write replaces it
+    container.write("data", "new").unwrap();
+    assert_eq!(container.read_data("data").unwrap().collect::<String>().unwrap(), "new");
+
+    // Replacing a container leaves no temporary files behind
+    write_database!((&database) /nested::data = 1u8).unwrap();
+    let nested = container.new_container("nested").unwrap();
+    assert!(nested.is_empty().unwrap());
+    assert_eq!(std::fs::read_dir(&path).unwrap().count(), 4); // `.meta`, `.lock`, `data` and `nested`
+}
+
+#[test]
+fn lazy_container_stream() {
+    use std::io::{Read, Seek, SeekFrom, Write};
+    let database = LazyDB::in_memory().unwrap();
+    let container = database.as_container().unwrap();
+    let og: Vec<u8> = (0..=255).cycle().take(100_000).collect();
+
+    // Stream a binary value in chunks
+    let mut writer = container.binary_writer("blob").unwrap();
+    for chunk in og.chunks(4096) { writer.write_all(chunk).unwrap() };
+    writer.finish().unwrap();
+    assert_eq!(*container.read_data("blob").unwrap().collect_binary().unwrap(), *og);
+
+    // Read it back incrementally (positions don't include the type byte)
+    let mut reader = container.read_data("blob").unwrap().into_reader();
+    let mut buffer = [0u8; 4];
+    reader.read_exact(&mut buffer).unwrap();
+    assert_eq!(buffer, [0, 1, 2, 3]);
+    assert_eq!(reader.seek(SeekFrom::Start(300)).unwrap(), 300);
+    reader.read_exact(&mut buffer).unwrap();
+    assert_eq!(buffer, og[300..304]);
+    assert_eq!(reader.seek(SeekFrom::End(-4)).unwrap(), og.len() as u64 - 4);
+    assert!(reader.seek(SeekFrom::Current(-100_000)).is_err());
+
+    // An unfinished writer leaves the old value
+    let mut writer = container.binary_writer("blob").unwrap();
+    writer.write_all(&[1, 2, 3]).unwrap();
+    drop(writer);
+    assert_eq!(container.read_data("blob").unwrap().collect_binary().unwrap().len(), og.len());
+
+    // Strings may split characters between writes, but must be valid utf-8
+    let mut writer = container.string_writer("text").unwrap();
+    let bytes = "héllo wörld".as_bytes();
+    writer.write_all(&bytes[..2]).unwrap();
+    writer.write_all(&bytes[2..]).unwrap();
+    writer.finish().unwrap();
+    assert_eq!(container.read_data("text").unwrap().collect_string().unwrap(), "héllo wörld");
+    let mut writer = container.string_writer("text").unwrap();
+    assert!(writer.write_all(&[0xFF]).is_err());
+    let mut writer = container.string_writer("text").unwrap();
+    writer.write_all(&bytes[..2]).unwrap();
+    assert!(writer.finish().is_err());
+}
+
+#[test]
+fn lazy_container_array_set() {
+    let tmp = new_env();
+    let database = LazyDB::init(tmp.get_path().join("database")).unwrap();
+    let mut logged = LazyDB::init(tmp.get_path().join("logged")).unwrap(); // in place
+    logged.enable_wal().unwrap();
+    let memory = LazyDB::in_memory().unwrap();
+
+    for container in [database.as_container().unwrap(), logged.as_container().unwrap(), memory.as_container().unwrap()] {
+        container.write("samples", [1.5f64, 2.5, 3.5].as_slice()).unwrap();
+        // Only the element is rewritten
+        container.array_set("samples", 1, -8.0f64).unwrap();
+        assert_eq!(*container.read_data("samples").unwrap().collect_f64_array().unwrap(), [1.5, -8.0, 3.5]);
+        // The index and element type are checked
+        assert!(matches!(container.array_set("samples", 3, 0.0f64), Err(LDBError::IndexOutOfBounds(3, 3))));
+        assert!(container.array_set("samples", 0, 0u64).is_err());
+        assert!(container.array_set("missing", 0, 0.0f64).is_err());
+    }
+}
+
+#[test]
+fn lazy_container_append_array() {
+    let tmp = new_env();
+    let database = LazyDB::init(tmp.get_path().join("database")).unwrap();
+    let mut logged = LazyDB::init(tmp.get_path().join("logged")).unwrap(); // in place
+    logged.enable_wal().unwrap();
+    let memory = LazyDB::in_memory().unwrap();
+
+    for container in [database.as_container().unwrap(), logged.as_container().unwrap(), memory.as_container().unwrap()] {
+        // Appending creates the array if it's missing
+        container.append_array("series", &[1u32, 2]).unwrap();
+        container.append_array("series", &[3u32]).unwrap();
+        container.append_array::<u32>("series", &[]).unwrap();
+        assert_eq!(*container.read_data("series").unwrap().collect_u32_array().unwrap(), [1, 2, 3]);
+        // The stored element type is checked
+        assert!(container.append_array("series", &[4u64]).is_err());
+        container.write("name", "series").unwrap();
+        assert!(container.append_array("name", &[4u32]).is_err());
+
+        assert_eq!(container.pop_array::<u32>("series").unwrap(), Some(3));
+        container.append_array("series", &[4u32, 5, 6]).unwrap();
+        container.truncate_array::<u32>("series", 3).unwrap();
+        container.truncate_array::<u32>("series", 10).unwrap();
+        assert_eq!(*container.read_data("series").unwrap().collect_u32_array().unwrap(), [1, 2, 4]);
+        container.truncate_array::<u32>("series", 0).unwrap();
+        assert_eq!(container.pop_array::<u32>("series").unwrap(), None);
+        assert_eq!(container.read_data("series").unwrap().array_len().unwrap(), 0);
+    }
+}
+
+#[test]
+fn lazy_container_pop_array_concurrently() {
+    let tmp = new_env();
+    let mut database = LazyDB::init(tmp.get_path().join("database")).unwrap();
+    database.enable_wal().unwrap();
+    let container = database.as_container().unwrap();
+    container.write("queue", (0..64u32).collect::<Vec<_>>().as_slice()).unwrap();
+
+    // Every element is popped exactly once
+    let mut popped: Vec<u32> = std::thread::scope(|scope| {
+        let threads: Vec<_> = (0..4).map(|_| scope.spawn(|| {
+            let mut popped = Vec::new();
+            while let Some(x) = container.pop_array::<u32>("queue").unwrap() { popped.push(x) };
+            popped
+        })).collect();
+        threads.into_iter().flat_map(|x| x.join().unwrap()).collect()
+    });
+    popped.sort();
+    assert_eq!(popped, (0..64).collect::<Vec<_>>());
+}
+
+#[test]
+fn lazy_container_pack_map() {
+    let tmp = new_env();
+    let database = LazyDB::init(tmp.get_path().join("database")).unwrap();
+    write_database!((&database) /people/Dave::age = 21u8).unwrap();
+    write_database!((&database) /people/Dave::name = "Dave").unwrap();
+    write_database!((&database) /people/Dave/address::city = "Sydney").unwrap();
+    let people = database.as_container().unwrap().read_container("people").unwrap();
+
+    // A container becomes a single file (with nested containers as nested maps)
+    people.pack_map("Dave").unwrap();
+    assert!(people.contains_data("Dave"));
+    assert_eq!(people.read_data("Dave").unwrap().map_keys().unwrap(), ["address", "age", "name"]);
+    assert_eq!(people.read_data("Dave").unwrap().map_get("age").unwrap().unwrap().collect_u8().unwrap(), 21);
+    let address = people.read_data("Dave").unwrap().map_get("address").unwrap().unwrap();
+    assert_eq!(address.map_get("city").unwrap().unwrap().collect_string().unwrap(), "Sydney");
+
+    // And back again
+    let dave = people.unpack_map("Dave").unwrap();
+    assert_eq!(dave.read_data("name").unwrap().collect_string().unwrap(), "Dave");
+    assert_eq!(dave.read_data("address").unwrap().lazy_type, LazyType::Map);
+    assert!(people.unpack_map("Dave").is_err());
+
+    // Keys can't escape the container
+    let mut map = LazyMap::new();
+    map.insert("../../escaped", 1u8).unwrap();
+    people.write("bad", &map).unwrap();
+    assert!(matches!(people.unpack_map("bad"), Err(LDBError::InvalidKey(_))));
+    assert!(people.contains_data("bad"));
+    assert!(matches!(map.write_to(&dave), Err(LDBError::InvalidKey(_))));
+    assert!(!tmp.get_path().join("escaped").exists());
+
+    // A failed unpack leaves the map (the second value's name is too long for the filesystem)
+    let mut map = LazyMap::new();
+    map.insert("a", 1u8).unwrap();
+    map.insert("b".repeat(300), 2u8).unwrap();
+    people.write("partial", &map).unwrap();
+    assert!(matches!(people.unpack_map("partial"), Err(LDBError::IOError(_))));
+    assert_eq!(people.read_data("partial").unwrap().map_keys().unwrap().len(), 2);
+    assert_eq!(std::fs::read_dir(tmp.get_path().join("database/people")).unwrap().count(), 3); // without the temporary container
+
+    // A failed pack leaves the container (the temporary map's name is too long for the filesystem)
+    let long = "c".repeat(250);
+    people.child_container(&long).unwrap().write("value", 1u8).unwrap();
+    assert!(matches!(people.pack_map(&long), Err(LDBError::IOError(_))));
+    assert_eq!(people.read_container(&long).unwrap().read_data("value").unwrap().collect_u8().unwrap(), 1);
+    assert_eq!(people.keys().unwrap().count(), 4);
+}
@@ -47,6 +47,217 @@ test_lazy_data! {
     (lazy_data_f32) [new_f32, collect_f32] 123.234f32;
     (lazy_data_f64) [new_f64, collect_f64] 123141234.1234f64;
     (lazy_data_bool) [new_bool, collect_bool] true;
+    (lazy_data_timestamp) [new_timestamp, collect_timestamp] LazyTimestamp::new(-1, 999_999_999).unwrap();
+    (lazy_data_duration) [new_duration, collect_duration] std::time::Duration::new(90061, 5);
+    (lazy_data_uuid) [new_uuid, collect_uuid] LazyUuid::from(0x67e5504410b1426f9247bb680e5fe0c8u128);
+    (lazy_data_char) [new_char, collect_char] 'ö';
+    (lazy_data_decimal) [new_decimal, collect_decimal] LazyDecimal::new(-1234567890123456789012345, 9).unwrap();
+}
+
+#[test]
+fn lazy_data_time() {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    let tmp = new_env();
+    let path = tmp.get_path().join("data.ld");
+    // `SystemTime`s before and after the unix epoch
+    for og in [UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789), UNIX_EPOCH - Duration::new(3, 250_000_000)] {
+        LazyData::write(FileWrapper::new_writer(File::create(&path).unwrap()), og).unwrap();
+        assert_eq!(LazyData::load(&path).unwrap().lazy_type, LazyType::Timestamp);
+        assert_eq!(LazyData::load(&path).unwrap().collect::<SystemTime>().unwrap(), og);
+    }
+    assert_eq!(LazyTimestamp::from(UNIX_EPOCH - Duration::new(3, 250_000_000)), LazyTimestamp::new(-4, 750_000_000).unwrap());
+    assert!(LazyTimestamp::new(0, 1_000_000_000).is_none());
+    // The type is kept
+    LazyData::write(FileWrapper::new_writer(File::create(&path).unwrap()), Duration::from_millis(1500)).unwrap();
+    assert!(LazyData::load(&path).unwrap().collect_timestamp().is_err());
+    assert!(LazyData::load(&path).unwrap().collect_i64().is_err());
+}
+
+#[test]
+fn lazy_data_decimal_exact() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("data.ld");
+    assert_eq!(LazyDecimal::new(-1999, 2).unwrap().to_string(), "-19.99");
+    assert_eq!(LazyDecimal::new(5, 3).unwrap().to_string(), "0.005");
+    assert_eq!(LazyDecimal::new(42, 0).unwrap().to_string(), "42");
+    assert_eq!(LazyDecimal::new(-125, 2).unwrap().to_f64(), -1.25);
+    // Scales beyond an `i128`'s digits are rejected
+    assert!(matches!(LazyDecimal::new(1, 39), Err(LDBError::InvalidValue(LazyType::Decimal, _))));
+    let invalid = LazyDecimal { mantissa: 1, scale: u32::MAX };
+    assert_eq!(invalid.to_string(), "1e-4294967295");
+    assert_eq!(invalid.to_f64(), 0.0);
+    assert!(LazyData::new_decimal(FileWrapper::new_writer(File::create(&path).unwrap()), invalid).is_err());
+    std::fs::write(&path, [&[u8::from(LazyType::Decimal)][..], &1i128.to_be_bytes(), &39u32.to_be_bytes()].concat()).unwrap();
+    assert!(matches!(LazyData::load(&path).unwrap().collect_decimal(), Err(LDBError::InvalidValue(LazyType::Decimal, _))));
+    // Invalid chars are rejected when they're collected
+    std::fs::write(&path, [u8::from(LazyType::Char), 0, 0, 0xD8, 0]).unwrap();
+    assert!(matches!(LazyData::load(&path).unwrap().collect_char(), Err(LDBError::InvalidValue(LazyType::Char, _))));
+}
+
+#[cfg(feature = "rust_decimal")]
+#[test]
+fn lazy_data_rust_decimal() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("data.ld");
+    let og = rust_decimal::Decimal::new(-100_000_000_001, 4);
+    LazyData::write(FileWrapper::new_writer(File::create(&path).unwrap()), og).unwrap();
+    assert_eq!(LazyData::load(&path).unwrap().collect_decimal().unwrap().to_string(), "-10000000.0001");
+    assert_eq!(LazyData::load(&path).unwrap().collect::<rust_decimal::Decimal>().unwrap(), og);
+    // Decimals that are too precise can't be converted
+    LazyData::new_decimal(FileWrapper::new_writer(File::create(&path).unwrap()), LazyDecimal::new(1, 30).unwrap()).unwrap();
+    assert!(LazyData::load(&path).unwrap().collect::<rust_decimal::Decimal>().is_err());
+}
+
+#[derive(Debug, PartialEq)]
+enum Shape {
+    Circle(f64),
+    Label(String),
+    Empty,
+}
+
+impl ToLazy for Shape {
+    fn to_lazy(&self, file: FileWrapper) -> Result<(), LDBError> {
+        match self {
+            Shape::Circle(x) => LazyData::new_enum(file, 0, "Circle", x),
+            Shape::Label(x) => LazyData::new_enum(file, 1, "Label", x),
+            Shape::Empty => LazyData::new_enum(file, 2, "Empty", &()),
+        }
+    }
+}
+
+impl FromLazy for Shape {
+    fn from_lazy(data: LazyData) -> Result<Self, LDBError> {
+        let variant = data.collect_enum()?;
+        match variant.name.as_str() {
+            "Circle" => Ok(Shape::Circle(variant.payload.collect()?)),
+            "Label" => Ok(Shape::Label(variant.payload.collect()?)),
+            _ => Ok(Shape::Empty),
+        }
+    }
+}
+
+#[test]
+fn lazy_data_option_enum() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("data.ld");
+    let write = |value: &dyn Fn(FileWrapper) -> Result<(), LDBError>| value(FileWrapper::new_writer(File::create(&path).unwrap())).unwrap();
+    // Options keep their inner type (and can be nested)
+    write(&|file| LazyData::write(file, Some(42u32)));
+    assert_eq!(LazyData::load(&path).unwrap().lazy_type, LazyType::Option);
+    assert_eq!(LazyData::load(&path).unwrap().collect::<Option<u32>>().unwrap(), Some(42));
+    assert!(LazyData::load(&path).unwrap().collect::<Option<i32>>().is_err());
+    write(&|file| LazyData::write(file, None::<u32>));
+    assert_eq!(LazyData::load(&path).unwrap().collect::<Option<u32>>().unwrap(), None);
+    write(&|file| LazyData::write(file, Some(None::<String>)));
+    assert_eq!(LazyData::load(&path).unwrap().collect::<Option<Option<String>>>().unwrap(), Some(None));
+    // Nested arrays can still be accessed lazily
+    write(&|file| LazyData::write(file, Some(vec![1u16, 2, 3])));
+    let mut array = LazyData::load(&path).unwrap().collect_option().unwrap().unwrap();
+    assert_eq!(array.array_len().unwrap(), 3);
+    assert_eq!(array.array_get::<u16>(2).unwrap(), 3);
+    // Enums keep their variant and payload
+    for og in [Shape::Circle(1.5), Shape::Label(String::from("Dave")), Shape::Empty] {
+        write(&|file| LazyData::write(file, &og));
+        assert_eq!(LazyData::load(&path).unwrap().collect::<Shape>().unwrap(), og);
+    }
+    let variant = LazyData::load(&path).unwrap().collect_enum().unwrap();
+    assert_eq!((variant.index, variant.name.as_str(), variant.payload.lazy_type), (2, "Empty", LazyType::Void));
+    assert!(format!("{variant:?}").contains("Empty"));
+    // A corrupt name length is rejected without allocating it
+    std::fs::write(&path, [&[LazyType::Enum.into()][..], &[0; 4], &u32::MAX.to_be_bytes(), b"Empty"].concat()).unwrap();
+    assert!(matches!(LazyData::load(&path).unwrap().collect_enum(), Err(LDBError::InvalidValue(LazyType::Enum, _))));
+}
+
+#[test]
+fn lazy_data_map() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("data.ld");
+    let mut og = LazyMap::new();
+    og.insert("name", "Dave").unwrap();
+    og.insert("age", 21u8).unwrap();
+    og.insert("scores", [9u16, 7, 10]).unwrap();
+    og.insert("age", 22u8).unwrap(); // replaces the value
+    let mut inner = LazyMap::new();
+    inner.insert("home", "Earth").unwrap();
+    og.insert("places", &inner).unwrap();
+    LazyData::write(FileWrapper::new_writer(File::create(&path).unwrap()), &og).unwrap();
+    // Individual values are looked up lazily
+    assert_eq!(LazyData::load(&path).unwrap().map_keys().unwrap(), ["name", "age", "scores", "places"]);
+    assert_eq!(LazyData::load(&path).unwrap().map_get("age").unwrap().unwrap().collect_u8().unwrap(), 22);
+    assert!(LazyData::load(&path).unwrap().map_get("missing").unwrap().is_none());
+    let mut scores = LazyData::load(&path).unwrap().map_get("scores").unwrap().unwrap();
+    assert_eq!(scores.array_get::<u16>(2).unwrap(), 10);
+    let places = LazyData::load(&path).unwrap().map_get("places").unwrap().unwrap();
+    assert_eq!(places.map_get("home").unwrap().unwrap().collect_string().unwrap(), "Earth");
+    // The whole map
+    let mut new = LazyData::load(&path).unwrap().collect::<LazyMap>().unwrap();
+    assert_eq!(new, og);
+    assert_eq!(new.get("name").unwrap().unwrap().collect_string().unwrap(), "Dave");
+    assert!(new.remove("name"));
+    assert!(!new.contains_key("name"));
+    assert_eq!(new.len(), 3);
+    assert!(LazyData::load(&path).unwrap().map_get("age").is_ok());
+    assert!(LazyData::load(&path).unwrap().collect_string().is_err());
+    // Corrupt key and value lengths are rejected without allocating them
+    for entry in [[&u32::MAX.to_be_bytes()[..], b"age"].concat(), [&3u32.to_be_bytes()[..], b"age", &u64::MAX.to_be_bytes(), &[22]].concat()] {
+        std::fs::write(&path, [&[LazyType::Map.into()][..], &entry].concat()).unwrap();
+        assert!(matches!(LazyData::load(&path).unwrap().collect::<LazyMap>(), Err(LDBError::InvalidValue(LazyType::Map, _))));
+        assert!(matches!(LazyData::load(&path).unwrap().map_get("name"), Err(LDBError::InvalidValue(LazyType::Map, _))));
+        assert!(matches!(LazyData::load(&path).unwrap().map_keys(), Err(LDBError::InvalidValue(LazyType::Map, _))));
+    }
+}
+
+#[test]
+fn lazy_data_tensor() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("data.ld");
+    let og: Vec<f32> = (0..24).map(|x| x as f32).collect();
+    LazyData::new_f32_tensor(FileWrapper::new_writer(File::create(&path).unwrap()), &[2, 3, 4], &og).unwrap();
+    // The whole tensor
+    let tensor = LazyData::load(&path).unwrap().collect_f32_tensor().unwrap();
+    assert_eq!(*tensor.shape, [2, 3, 4]);
+    assert_eq!(*tensor.data, *og);
+    assert_eq!(tensor.get(&[1, 2, 3]), Some(23.0));
+    assert_eq!(tensor.get(&[1, 3, 0]), None);
+    // Lazily by row
+    let mut data = LazyData::load(&path).unwrap();
+    assert_eq!(*data.tensor_shape::<f32>().unwrap(), [2, 3, 4]);
+    let row = data.tensor_row::<f32>(1).unwrap();
+    assert_eq!(*row.shape, [3, 4]);
+    assert_eq!(*row.data, og[12..]);
+    let rows = data.tensor_rows::<f32>(0..1).unwrap();
+    assert_eq!(*rows.shape, [1, 3, 4]);
+    assert_eq!(*rows.data, og[..12]);
+    assert!(matches!(data.tensor_row::<f32>(2), Err(LDBError::IndexOutOfBounds(2, 2))));
+    assert!(matches!(data.tensor_row::<f32>(usize::MAX), Err(LDBError::IndexOutOfBounds(usize::MAX, 2))));
+    assert!(data.tensor_row::<f64>(0).is_err());
+    // Generic and validated
+    let generic = LazyTensor::new([2, 2], [1u8, 2, 3, 4]).unwrap();
+    LazyData::write(FileWrapper::new_writer(File::create(&path).unwrap()), &generic).unwrap();
+    assert_eq!(LazyData::load(&path).unwrap().collect::<LazyTensor<u8>>().unwrap(), generic);
+    assert!(LazyTensor::new([2, 2], [1u8, 2, 3]).is_err());
+    assert!(matches!(LazyTensor::new([usize::MAX, 2], [1u8]), Err(LDBError::InvalidValue(..))));
+    assert!(LazyData::load(&path).unwrap().collect_u8_array().is_err());
+    assert!(LazyData::new_u16_tensor(FileWrapper::new_writer(File::create(&path).unwrap()), &[usize::MAX, 2, 0], &[]).is_ok());
+    // A corrupt shape is rejected without allocating it
+    std::fs::write(&path, [&[LazyType::Tensor.into(), LazyType::F32.into(), 1][..], &(1u64 << 40).to_be_bytes(), &[0; 8]].concat()).unwrap();
+    assert!(matches!(LazyData::load(&path).unwrap().collect_f32_tensor(), Err(LDBError::InvalidValue(LazyType::Tensor, _))));
+    assert!(matches!(LazyData::load(&path).unwrap().tensor_rows::<f32>(0..2), Err(LDBError::InvalidValue(LazyType::Tensor, _))));
+    assert!(matches!(LazyData::load(&path).unwrap().tensor_row::<f32>(0), Err(LDBError::InvalidValue(LazyType::Tensor, _))));
+}
+
+#[cfg(all(feature = "chrono", feature = "uuid"))]
+#[test]
+fn lazy_data_chrono_uuid() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("data.ld");
+    let og = chrono::DateTime::from_timestamp(1_700_000_000, 42).unwrap();
+    LazyData::write(FileWrapper::new_writer(File::create(&path).unwrap()), og).unwrap();
+    assert_eq!(LazyData::load(&path).unwrap().collect::<chrono::DateTime<chrono::Utc>>().unwrap(), og);
+    let og = uuid::Uuid::from_u128(0x67e5504410b1426f9247bb680e5fe0c8);
+    LazyData::write(FileWrapper::new_writer(File::create(&path).unwrap()), og).unwrap();
+    assert_eq!(LazyData::load(&path).unwrap().collect::<uuid::Uuid>().unwrap(), og);
+    assert_eq!(u128::from(LazyData::load(&path).unwrap().collect_uuid().unwrap()), og.as_u128());
 }
 
 #[test]
@@ -73,11 +284,67 @@ fn lazy_data_link() {
     write_database!((&database) link = new_link("nested/data")).unwrap();
 
     // Reading the link
-    let data = search_database!((&database) link).unwrap().collect_link(database).unwrap();
+    let data = database.as_container().unwrap().read_data("link").unwrap().collect_link(&database).unwrap();
     let new_data = data.collect_string().unwrap();
 
     // Values must be equal
     assert_eq!(old_data, new_data);
+
+    // Followed transparently by the macros
+    assert_eq!(search_database!((&database) link).unwrap().collect_string().unwrap(), old_data);
+    write_database!((&database) /nested::sibling = new_link("data")).unwrap(); // relative to it's container
+    write_database!((&database) /other::parent = new_link("../nested/sibling")).unwrap(); // a chain of links
+    write_database!((&database) /other::absolute = new_link("/nested/data")).unwrap();
+    assert_eq!(search_database!((&database) /other::parent).unwrap().collect_string().unwrap(), old_data);
+    assert_eq!(search_database!((&database) /other::absolute).unwrap().collect_string().unwrap(), old_data);
+
+    // Links to containers
+    write_database!((&database) dir = new_link("nested")).unwrap();
+    assert_eq!(search_database!((&database) /dir::data).unwrap().collect_string().unwrap(), old_data);
+    let link = database.as_container().unwrap().read_data("dir").unwrap();
+    assert!(database.resolve_link(link).unwrap().into_container().unwrap().contains_data("sibling"));
+    assert!(search_database!((&database) dir).is_err());
+
+    // Cycles, dangling links and escaping the database
+    write_database!((&database) a = new_link("b")).unwrap();
+    write_database!((&database) b = new_link("a")).unwrap();
+    assert!(matches!(search_database!((&database) a), Err(LDBError::LinkCycle(_))));
+    write_database!((&database) dangling = new_link("missing")).unwrap();
+    assert!(matches!(search_database!((&database) dangling), Err(LDBError::FileNotFound(_))));
+    write_database!((&database) outside = new_link("../data")).unwrap();
+    assert!(search_database!((&database) outside).is_err());
+    for i in 0..=MAX_LINK_DEPTH {
+        write_database!((&database) (format!("chain{i}")) = new_link(format!("chain{}", i + 1))).unwrap();
+    }
+    write_database!((&database) (format!("chain{}", MAX_LINK_DEPTH + 1)) = new_u8(1)).unwrap();
+    assert!(matches!(search_database!((&database) chain0), Err(LDBError::LinkDepthExceeded(_, MAX_LINK_DEPTH))));
+    assert_eq!(search_database!((&database) chain1).unwrap().collect_u8().unwrap(), 1);
+}
+
+#[test]
+fn lazy_data_legacy_link() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("database");
+    let database = LazyDB::init(&path).unwrap();
+    write_database!((&database) /nested::data = new_u8(1)).unwrap();
+    write_database!((&database) /other::link = new_link("nested/data")).unwrap();
+    write_database!((&database) root = new_link("nested/data")).unwrap();
+    drop(database);
+
+    // Databases of older versions resolved every link from the root
+    let old_version = |version: &[u8]| LazyData::new_binary(FileWrapper::new_writer(File::create(path.join(".meta")).unwrap()), version).unwrap();
+    old_version(&[1, 2, 1]);
+    let database = LazyDB::open_read_only(&path).unwrap();
+    assert_eq!(search_database!((&database) /other::link).unwrap().collect_u8().unwrap(), 1);
+    drop(database);
+
+    // Loading them rewrites their nested links so they keep their meaning
+    let database = LazyDB::load_dir(&path).unwrap();
+    assert_eq!(search_database!((&database) /other::link).unwrap().collect_u8().unwrap(), 1);
+    assert_eq!(search_database!((&database) root).unwrap().collect_u8().unwrap(), 1);
+    let link = database.as_container().unwrap().read_container("other").unwrap().read_data("link").unwrap();
+    assert_eq!(link.collect_link_path().unwrap(), std::path::Path::new("/nested/data"));
+    assert_eq!(LazyData::load(path.join(".meta")).unwrap().collect_binary().unwrap().as_ref(), [VERSION.major, VERSION.minor, VERSION.build]);
 }
 
 #[test]
@@ -93,4 +360,117 @@ fn lazy_data_array() {
     let new = LazyData::load(path).unwrap().collect_i32_array().unwrap();
     // Values must be the same
     let _ = og.iter().enumerate().map(|(i, x)| assert_eq!(*x, new[i]));
-}
\ No newline at end of file
+}
+#[test]
+fn lazy_data_array_lazy() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("data.ld");
+    let og: Vec<u32> = (0..20_000).map(|x| x * 3).collect();
+    LazyData::new_u32_array(FileWrapper::new_writer(File::create(&path).unwrap()), &og).unwrap();
+    // Individual elements and ranges
+    let mut data = LazyData::load(&path).unwrap();
+    assert_eq!(data.array_len().unwrap(), og.len());
+    assert_eq!(data.array_get::<u32>(10_000).unwrap(), og[10_000]);
+    assert_eq!(data.array_get::<u32>(0).unwrap(), og[0]);
+    assert_eq!(*data.array_range::<u32>(500..510).unwrap(), og[500..510]);
+    assert!(matches!(data.array_get::<u32>(20_000), Err(LDBError::IndexOutOfBounds(20_000, 20_000))));
+    assert!(data.array_range::<u32>(19_990..20_001).is_err());
+    assert!(data.array_get::<i32>(0).is_err());
+    // Lazy iteration (skipping seeks past elements)
+    let iter = LazyData::load(&path).unwrap().array_iter::<u32>().unwrap();
+    assert_eq!(iter.len(), og.len());
+    let skipped: Vec<u32> = iter.skip(19_998).map(|x| x.unwrap()).collect();
+    assert_eq!(skipped, og[19_998..]);
+    let mut iter = LazyData::load(&path).unwrap().array_iter::<u32>().unwrap();
+    assert_eq!(iter.next().unwrap().unwrap(), og[0]);
+    assert_eq!(iter.nth(99).unwrap().unwrap(), og[100]);
+    assert_eq!(iter.next().unwrap().unwrap(), og[101]);
+    assert!(iter.nth(20_000).is_none());
+}
+
+#[test]
+fn lazy_data_array_variable() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("data.ld");
+    // Strings (with lazy element access)
+    let og = ["Dave", "", "héllo", "Earth"];
+    LazyData::new_string_array(FileWrapper::new_writer(File::create(&path).unwrap()), &og).unwrap();
+    assert_eq!(*LazyData::load(&path).unwrap().collect_string_array().unwrap(), og);
+    assert_eq!(LazyData::load(&path).unwrap().array_len().unwrap(), 4);
+    assert_eq!(LazyData::load(&path).unwrap().array_get_string(2).unwrap(), "héllo");
+    assert!(matches!(LazyData::load(&path).unwrap().array_get_string(4), Err(LDBError::IndexOutOfBounds(4, 4))));
+    assert!(LazyData::load(&path).unwrap().array_get_binary(0).is_err());
+    let skipped: Vec<String> = LazyData::load(&path).unwrap().var_array_iter::<String>().unwrap().skip(3).map(|x| x.unwrap()).collect();
+    assert_eq!(skipped, ["Earth"]);
+    // Binaries (larger than the read buffer)
+    let og: Vec<Vec<u8>> = (0..50).map(|x| vec![x as u8; x * 1000]).collect();
+    LazyData::write(FileWrapper::new_writer(File::create(&path).unwrap()), &og).unwrap();
+    assert_eq!(LazyData::load(&path).unwrap().collect::<Vec<Vec<u8>>>().unwrap(), og);
+    assert_eq!(*LazyData::load(&path).unwrap().array_get_binary(49).unwrap(), *og[49]);
+    assert_eq!(LazyData::load(&path).unwrap().array_len().unwrap(), 50);
+    // A corrupt element length is rejected without allocating it
+    std::fs::write(&path, [&[LazyType::Array.into(), LazyType::String.into()][..], &4u64.to_be_bytes(), b"Dave", &u64::MAX.to_be_bytes(), b"Earth"].concat()).unwrap();
+    assert!(matches!(LazyData::load(&path).unwrap().collect_string_array(), Err(LDBError::InvalidValue(LazyType::Array, _))));
+    assert!(matches!(LazyData::load(&path).unwrap().array_get_string(1), Err(LDBError::InvalidValue(LazyType::Array, _))));
+    assert!(matches!(LazyData::load(&path).unwrap().array_len(), Err(LDBError::InvalidValue(LazyType::Array, _))));
+    assert_eq!(LazyData::load(&path).unwrap().array_get_string(0).unwrap(), "Dave");
+    // Booleans are packed into bits
+    let og: Vec<bool> = (0..13).map(|x| x % 3 == 0).collect();
+    LazyData::write(FileWrapper::new_writer(File::create(&path).unwrap()), &og).unwrap();
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), 5);
+    assert_eq!(LazyData::load(&path).unwrap().collect::<Vec<bool>>().unwrap(), og);
+    let mut data = LazyData::load(&path).unwrap();
+    assert_eq!(data.array_len().unwrap(), 13);
+    assert!(data.array_get_bool(12).unwrap());
+    assert!(!data.array_get_bool(11).unwrap());
+    assert!(data.array_get_bool(13).is_err());
+    // Empty arrays
+    LazyData::new_bool_array(FileWrapper::new_writer(File::create(&path).unwrap()), &[]).unwrap();
+    assert!(LazyData::load(&path).unwrap().collect_bool_array().unwrap().is_empty());
+}
+
+#[test]
+fn lazy_data_generic() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("data.ld");
+    let og = vec![1.5f64, -2.25, 1024.0];
+    // Write to file without specifying the type
+    let file = FileWrapper::new_writer(File::create(&path).unwrap());
+    LazyData::write(file, &og).unwrap();
+    // Load file with the type inferred
+    let new: Vec<f64> = LazyData::load(&path).unwrap().collect().unwrap();
+    // Values must be the same
+    assert_eq!(og, new);
+    // Collecting as the wrong type must fail
+    assert!(LazyData::load(path).unwrap().collect::<String>().is_err());
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn lazy_data_mmap() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("data.ld");
+    let og_binary: Vec<u8> = (0..=255).cycle().take(100_000).collect();
+    let og_array = [1.5f64, -2.25, 1024.0];
+    // Map binary without reading it into memory
+    // SAFETY (for all the mappings below): the files are only ever replaced, never modified in place
+    LazyData::new_binary(FileWrapper::new_writer(File::create(&path).unwrap()), &og_binary).unwrap();
+    let mapped = unsafe { LazyData::load(&path).unwrap().map_binary() }.unwrap();
+    assert_eq!(*mapped, *og_binary);
+    // Writing replaces the file, so the mapping still sees the old data
+    LazyData::new_f64_array(FileWrapper::new_atomic(&path).unwrap(), &og_array).unwrap();
+    assert_eq!(*mapped, *og_binary);
+    // Typed array views
+    let array = unsafe { LazyData::load(&path).unwrap().map_f64_array() }.unwrap();
+    assert_eq!(array.len(), 3);
+    assert_eq!(array.get(1), Some(-2.25));
+    assert_eq!(array.get(3), None);
+    assert_eq!(array.iter().collect::<Vec<_>>(), og_array);
+    assert!(unsafe { LazyData::load(&path).unwrap().map_u32_array() }.is_err());
+    assert!(unsafe { LazyData::load(&path).unwrap().map_binary() }.is_err());
+    // Data that isn't on the filesystem is read into memory instead
+    let database = LazyDB::in_memory().unwrap();
+    write_database!((&database) bytes = new_u8_array(&[1, 2, 3])).unwrap();
+    let array = unsafe { search_database!((&database) bytes).unwrap().map_u8_array() }.unwrap();
+    assert_eq!(*array, [1, 2, 3]);
+}
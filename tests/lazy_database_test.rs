@@ -67,4 +67,346 @@ fn _lazy_database_stress_test() {
         write_database!((&database) /root::(i.to_string()) = new_u64(gen_random())).unwrap();
     }}
     std::io::stdin().read_line(&mut String::new()).unwrap();
-}
\ No newline at end of file
+}
+#[test]
+fn lazy_database_generic() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("database");
+    let og_string = String::from("Hello world!");
+
+    // Writing to the database without `new_<type>` functions
+    let database = LazyDB::init(&path).unwrap();
+    write_database!((&database) /nested::string = &og_string).unwrap();
+    write_database!((&database) number = 42u64).unwrap();
+    database.as_container().unwrap().write("flag", true).unwrap();
+
+    // Read from the database with `collect`
+    let new_string: String = search_database!((&database) /nested::string).unwrap().collect().unwrap();
+    let number: u64 = search_database!((&database) number).unwrap().collect().unwrap();
+    let flag: bool = search_database!((&database) flag).unwrap().collect().unwrap();
+
+    // Must be equal
+    assert_eq!(og_string, new_string);
+    assert_eq!(number, 42);
+    assert!(flag);
+}
+
+#[test]
+fn lazy_database_wal() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("database");
+    let wal = path.join(".wal");
+
+    // Mutations are logged and the log is emptied once they're all finished
+    let mut database = LazyDB::init(&path).unwrap();
+    database.enable_wal().unwrap();
+    write_database!((&database) /nested::data = 1u8).unwrap();
+    write_database!((&database) gone = 2u8).unwrap();
+    assert!(std::fs::metadata(&wal).unwrap().len() > 0);
+    drop(database);
+    assert_eq!(std::fs::metadata(&wal).unwrap().len(), 0);
+
+    // Simulate a crash with a committed write, an uncommitted write and an unfinished removal
+    LazyData::new_u8(FileWrapper::new_writer(std::fs::File::create(path.join(".committed")).unwrap()), 3).unwrap();
+    LazyData::new_u8(FileWrapper::new_writer(std::fs::File::create(path.join(".uncommitted")).unwrap()), 4).unwrap();
+    // (and an interrupted in-place write and truncation of an array, which are redone)
+    LazyData::new_u8_array(FileWrapper::new_writer(std::fs::File::create(path.join("array")).unwrap()), &[1, 2, 3]).unwrap();
+    let records: &[&[&str]] = &[
+        &["B", "0", "W", ".committed", "nested/data"], &["C", "0"], &["B", "1", "W", ".uncommitted", "other"], &["B", "2", "R", "gone"],
+        &["B", "3", "S", "array", "2", "\x09"], &["B", "4", "T", "array", "4"],
+    ];
+    let mut log = Vec::new();
+    for record in records {
+        let fields: Vec<u8> = record.iter().flat_map(|x| (x.len() as u32).to_be_bytes().into_iter().chain(x.bytes())).collect();
+        log.extend((fields.len() as u32).to_be_bytes());
+        log.extend(fields);
+    }
+    log.extend(b"\0\0\0\x20\0\0\0\x01B"); // torn
+    std::fs::write(&wal, log).unwrap();
+
+    // Loading finishes or discards them
+    let database = LazyDB::load_dir(&path).unwrap();
+    assert!(database.wal_enabled());
+    assert_eq!(search_database!((&database) /nested::data).unwrap().collect::<u8>().unwrap(), 3);
+    assert!(search_database!((&database) other).is_err());
+    assert!(search_database!((&database) gone).is_err());
+    assert!(!path.join(".committed").exists());
+    assert!(!path.join(".uncommitted").exists());
+    assert_eq!(*search_database!((&database) array).unwrap().collect_u8_array().unwrap(), [9, 2]);
+    assert_eq!(std::fs::metadata(&wal).unwrap().len(), 0);
+}
+
+#[test]
+fn lazy_database_lock() {
+    use std::time::Duration;
+    let tmp = new_env();
+    let path = tmp.get_path().join("database");
+
+    // Handles within the same process share the lock
+    let database = LazyDB::init(&path).unwrap();
+    let again = LazyDB::load_dir(&path).unwrap();
+    drop((database, again));
+
+    // Simulate another process holding the lock exclusively
+    let other = std::fs::File::open(path.join(".lock")).unwrap();
+    other.lock().unwrap();
+    assert!(matches!(LazyDB::load_dir(&path), Err(LDBError::DatabaseLocked(_))));
+    assert!(matches!(LazyDB::load_dir_with(&path, LockMode::Shared, LockWait::Timeout(Duration::from_millis(50))), Err(LDBError::DatabaseLocked(_))));
+    other.unlock().unwrap();
+
+    // Shared locks can be held by many readers but not alongside a writer
+    other.lock_shared().unwrap();
+    let reader = LazyDB::load_dir_with(&path, LockMode::Shared, LockWait::Try).unwrap();
+    assert!(matches!(LazyDB::load_dir(&path), Err(LDBError::DatabaseLocked(_))));
+    other.unlock().unwrap();
+    assert!(matches!(LazyDB::load_dir(&path), Err(LDBError::DatabaseLocked(_)))); // the reader still holds it
+    drop(reader);
+    LazyDB::load_dir_with(&path, LockMode::Exclusive, LockWait::Block).unwrap();
+
+    // Waiting on one database doesn't hold up locking others
+    other.lock().unwrap();
+    let waiting = std::thread::spawn(move || LazyDB::load_dir_with(&path, LockMode::Exclusive, LockWait::Timeout(Duration::from_millis(500))).is_err());
+    std::thread::sleep(Duration::from_millis(50));
+    let start = std::time::Instant::now();
+    LazyDB::init(tmp.get_path().join("unrelated")).unwrap();
+    assert!(start.elapsed() < Duration::from_millis(250));
+    assert!(waiting.join().unwrap());
+
+    // Missing parents of compiled databases are created
+    LazyDB::init_db(tmp.get_path().join("missing/parent/database")).unwrap();
+}
+
+#[test]
+fn lazy_database_compile_lock() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("database");
+    LazyDB::init_db(&path).unwrap();
+
+    // Another process loading the compiled database must not decompile it at the same time
+    let other = std::fs::File::open(path.with_extension("lock")).unwrap();
+    other.lock().unwrap();
+    assert!(matches!(LazyDB::load_db(path.with_extension("ldb")), Err(LDBError::DatabaseLocked(_))));
+    assert!(!path.with_extension("modb").exists());
+    other.unlock().unwrap();
+    LazyDB::load_db(path.with_extension("ldb")).unwrap();
+}
+
+#[test]
+fn lazy_database_read_only() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("database");
+
+    // Directory databases
+    let database = LazyDB::init(&path).unwrap();
+    write_database!((&database) /nested::data = 1u8).unwrap();
+    drop(database);
+    let mut database = LazyDB::open_read_only(&path).unwrap();
+    assert!(database.is_read_only());
+    assert_eq!(search_database!((&database) /nested::data).unwrap().collect::<u8>().unwrap(), 1);
+    assert!(matches!(write_database!((&database) /nested::data = 2u8), Err(LDBError::ReadOnly(_))));
+    assert!(matches!(write_database!((&database) /other::data = 2u8), Err(LDBError::DirNotFound(_)))); // missing containers aren't created
+    assert!(matches!(search_database!((&database) /other::data), Err(LDBError::DirNotFound(_))));
+    assert!(matches!(database.as_container().unwrap().remove("nested"), Err(LDBError::ReadOnly(_))));
+    assert!(matches!(database.transaction(|_| Ok(())), Err(LDBError::ReadOnly(_))));
+    assert!(matches!(database.enable_wal(), Err(LDBError::ReadOnly(_))));
+    let reader = LazyDB::open_read_only(&path).unwrap(); // many readers at once
+    assert!(matches!(LazyDB::load_dir(&path), Err(LDBError::DatabaseLocked(_))));
+    drop((database, reader));
+
+    // Compiled databases are never recompiled and leave no `.modb` behind
+    let path = tmp.get_path().join("compiled");
+    let database = LazyDB::init_db(&path).unwrap();
+    write_database!((&database) data = "Hello world!").unwrap();
+    drop(database);
+    let ldb = path.with_extension("ldb");
+    let modified = std::fs::metadata(&ldb).unwrap().modified().unwrap();
+    let database = LazyDB::open_read_only(&ldb).unwrap();
+    let reader = LazyDB::open_read_only(&ldb).unwrap();
+    assert_eq!(search_database!((&reader) data).unwrap().collect::<String>().unwrap(), "Hello world!");
+    assert!(matches!(write_database!((&database) data = "changed"), Err(LDBError::ReadOnly(_))));
+    assert!(!path.with_extension("modb").exists());
+    drop((database, reader));
+    assert_eq!(std::fs::metadata(&ldb).unwrap().modified().unwrap(), modified);
+}
+
+#[test]
+fn lazy_database_archive() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("database");
+    let ldb = path.with_extension("ldb");
+
+    // Compile a database
+    let mut database = LazyDB::init_db(&path).unwrap();
+    write_database!((&database) /nested/deeper::data = "Hello world!").unwrap();
+    write_database!((&database) number = 42u32).unwrap();
+    database.as_container().unwrap().child_container("empty").unwrap();
+    database.as_container().unwrap().write_link("link", "number").unwrap();
+    database.enable_wal().unwrap();
+    std::fs::write(path.with_extension("modb").join(".number.tmp-1-2"), []).unwrap(); // left by a crash
+    drop(database);
+
+    // Read single keys straight out of the compiled file
+    let backend = ArchiveBackend::open(&ldb).unwrap();
+    assert_eq!(LazyData::load_from(&backend, "/number").unwrap().collect::<u32>().unwrap(), 42);
+    assert_eq!(backend.kind(std::path::Path::new("/empty")), Some(EntryKind::Container));
+    assert!(backend.open_writer(std::path::Path::new("/number")).is_err());
+    // Only the database's data is compiled (not it's logs, indexes or temporary files)
+    let mut hidden = backend.list(std::path::Path::new("/")).unwrap().into_iter().map(|(x, _)| x).filter(|x| x.starts_with('.')).collect::<Vec<_>>();
+    hidden.sort();
+    assert_eq!(hidden, [".meta"]);
+
+    let database = LazyDB::open_read_only(&ldb).unwrap();
+    assert_eq!(search_database!((&database) /nested/deeper::data).unwrap().collect::<String>().unwrap(), "Hello world!");
+    assert_eq!(database.as_container().unwrap().keys().unwrap().collect::<Vec<_>>(), ["empty", "link", "nested", "number"]);
+    assert!(matches!(write_database!((&database) number = 1u32), Err(LDBError::ReadOnly(_))));
+    assert_eq!(std::fs::read_dir(tmp.get_path()).unwrap().count(), 2); // only the `.ldb` and `.lock` files
+    drop(database);
+
+    // Loading it still decompiles it for writing
+    let database = LazyDB::load_db(&ldb).unwrap();
+    assert!(path.with_extension("modb").join("empty").is_dir());
+    assert_eq!(search_database!((&database) number).unwrap().collect::<u32>().unwrap(), 42);
+}
+
+#[test]
+fn lazy_database_corrupt_archive() {
+    let tmp = new_env();
+    let ldb = tmp.get_path().join("corrupt.ldb");
+    let archive = |block: &[u8], len: u64| {
+        let mut bytes = b"LAZYDBX\x01".to_vec();
+        bytes.extend(block);
+        let offset = bytes.len() as u64;
+        bytes.extend(1u64.to_le_bytes());
+        bytes.extend([0]);
+        bytes.extend(1u32.to_le_bytes());
+        bytes.extend(b"x");
+        bytes.extend(8u64.to_le_bytes());
+        bytes.extend(len.to_le_bytes());
+        bytes.extend(offset.to_le_bytes());
+        std::fs::write(&ldb, bytes).unwrap();
+        ArchiveBackend::open(&ldb).unwrap()
+    };
+
+    // Blocks outside of the file and sizes they couldn't decompress to are rejected (without allocating them)
+    assert!(LazyData::load_from(&archive(&[], u64::MAX / 2), "/x").is_err());
+    assert!(LazyData::load_from(&archive(&[0xff, 0xff, 0xff, 0xff, 0], 5), "/x").is_err());
+}
+
+#[test]
+fn lazy_database_legacy_archive() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("database");
+
+    // Databases compiled by older versions are a lz4 compressed tarball
+    let database = LazyDB::init(&path).unwrap();
+    write_database!((&database) /nested::data = "Hello world!").unwrap();
+    drop(database);
+    let ldb = tmp.get_path().join("legacy.ldb");
+    let mut builder = tar::Builder::new(lz4_flex::frame::FrameEncoder::new(std::fs::File::create(&ldb).unwrap()));
+    builder.append_dir_all("", &path).unwrap();
+    builder.into_inner().unwrap().finish().unwrap();
+
+    let database = LazyDB::open_read_only(&ldb).unwrap();
+    assert_eq!(search_database!((&database) /nested::data).unwrap().collect::<String>().unwrap(), "Hello world!");
+    let copy = database.path().to_path_buf(); // decompiled into a temporary directory
+    drop(database);
+    assert!(!copy.exists());
+    let database = LazyDB::load_db(&ldb).unwrap();
+    assert_eq!(search_database!((&database) /nested::data).unwrap().collect::<String>().unwrap(), "Hello world!");
+    drop(database);
+    assert!(ArchiveBackend::is_indexed(&ldb)); // recompiled in the new format
+}
+
+#[test]
+fn lazy_database_link_integrity() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("database");
+    let mut database = LazyDB::init(&path).unwrap();
+    let root = database.as_container().unwrap();
+    write_database!((&database) /people/Dave::age = 21u8).unwrap();
+    write_database!((&database) /places::home = "Earth").unwrap();
+    root.write_link("oldest", "people/Dave/age").unwrap();
+    root.child_container("people").unwrap().write_link("home", "../places").unwrap();
+    root.write_link("oldest_again", "oldest").unwrap(); // a link to a link
+    write_database!((&database) dangling = new_link("missing")).unwrap();
+    assert!(root.write_link("outside", "../outside").is_err());
+
+    // Only the dangling link is broken
+    let broken = database.check_links().unwrap();
+    assert_eq!(broken.len(), 1);
+    assert_eq!(broken[0].location.to_string(), "/::dangling");
+    assert_eq!(broken[0].target, std::path::Path::new("missing"));
+    assert!(matches!(broken[0].error, LDBError::FileNotFound(_)));
+
+    // Restrict refuses to remove data (or containers of data) that links point to
+    assert!(matches!(root.remove_with("places", RemovePolicy::Restrict), Err(LDBError::StillLinked(_, _))));
+    assert!(matches!(root.remove_with("people", RemovePolicy::Restrict), Err(LDBError::StillLinked(_, _))));
+    assert!(root.contains_container("people"));
+    root.remove_with("dangling", RemovePolicy::Restrict).unwrap();
+
+    // Cascade also removes the links (and the links to them)
+    root.child_container("people").unwrap().remove_with("Dave", RemovePolicy::Cascade).unwrap();
+    assert!(!root.contains_data("oldest"));
+    assert!(!root.contains_data("oldest_again"));
+    assert!(root.child_container("people").unwrap().contains_data("home"));
+
+    // Ignore leaves the links dangling
+    root.remove("places").unwrap();
+    let broken = database.check_links().unwrap();
+    assert_eq!(broken.len(), 1);
+    assert_eq!(broken[0].location.to_string(), "/people::home");
+
+    // Links written without `write_link` (by the macros or within transactions) are found too
+    write_database!((&database) count = 2u32).unwrap();
+    write_database!((&database) by_macro = new_link("count")).unwrap();
+    assert!(matches!(root.remove_with("count", RemovePolicy::Restrict), Err(LDBError::StillLinked(_, _))));
+    root.remove("by_macro").unwrap();
+    database.transaction(|tx| LazyData::new_link(tx.data_writer("by_transaction")?, "count")).unwrap();
+    assert!(matches!(root.remove_with("count", RemovePolicy::Restrict), Err(LDBError::StillLinked(_, _))));
+    root.remove_with("count", RemovePolicy::Cascade).unwrap();
+    assert!(!root.contains_data("by_transaction"));
+
+    // Links are found through the index, which only links written outside of the database are missing from until reindexed
+    assert!(path.join(".links").is_file());
+    write_database!((&database) count = 2u32).unwrap();
+    LazyData::new_link(FileWrapper::new_atomic(path.join("unindexed")).unwrap(), "count").unwrap();
+    root.remove_with("count", RemovePolicy::Restrict).unwrap();
+    write_database!((&database) count = 2u32).unwrap();
+    database.reindex_links().unwrap();
+    assert!(matches!(root.remove_with("count", RemovePolicy::Restrict), Err(LDBError::StillLinked(_, _))));
+    write_database!((&database) unindexed = 1u8).unwrap(); // no longer a link
+    root.remove_with("count", RemovePolicy::Restrict).unwrap();
+
+    // Links unpacked from a map are indexed where they end up
+    write_database!((&database) count = 2u32).unwrap();
+    root.child_container("inner").unwrap().write_link("link", "../count").unwrap();
+    root.pack_map("inner").unwrap();
+    root.remove_with("count", RemovePolicy::Restrict).unwrap(); // the link is now within a map
+    write_database!((&database) count = 2u32).unwrap();
+    root.unpack_map("inner").unwrap();
+    assert!(matches!(root.remove_with("count", RemovePolicy::Restrict), Err(LDBError::StillLinked(_, _))));
+}
+
+#[test]
+fn lazy_database_leftovers() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("database");
+    let database = LazyDB::init(&path).unwrap();
+    write_database!((&database) /nested::data = 1u8).unwrap();
+    database.as_container().unwrap().new_container("nested").unwrap(); // replaced with an empty container
+    assert!(database.as_container().unwrap().read_container("nested").unwrap().is_empty().unwrap());
+    drop(database);
+
+    // Simulate a crash part way through replacing a container and writing data
+    std::fs::create_dir(path.join(".swapped.old-1-2")).unwrap();
+    std::fs::write(path.join("nested/.data.tmp-1-3"), [0]).unwrap();
+    std::fs::create_dir(path.join("nested/.inner.tmp-4-5")).unwrap();
+    std::fs::write(path.join(".not-a-leftover"), [0]).unwrap();
+
+    LazyDB::load_dir(&path).unwrap();
+    assert!(!path.join(".swapped.old-1-2").exists());
+    assert!(path.join("swapped").is_dir()); // the old entry is put back
+    assert!(!path.join("nested/.data.tmp-1-3").exists());
+    assert!(!path.join("nested/.inner.tmp-4-5").exists());
+    assert!(path.join(".not-a-leftover").exists());
+}
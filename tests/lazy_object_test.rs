@@ -0,0 +1,60 @@
+mod isol;
+use isol::*;
+use lazy_db::*;
+use lazy_db_derive::LazyObject;
+
+#[derive(LazyObject)]
+struct Person {
+    container: LazyContainer,
+    dirty: DirtyFields,
+    name: Option<String>,
+    #[lazy(rename = "years")]
+    age: Option<u8>,
+    #[lazy(child)]
+    address: Option<Address>,
+}
+
+#[derive(LazyObject)]
+struct Address {
+    container: LazyContainer,
+    street: Option<String>,
+}
+
+#[test]
+fn lazy_object_derive() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("database");
+    let database = LazyDB::init(&path).unwrap();
+
+    // Writing the object (stored on drop)
+    {
+        let mut dave = Person::load_lazy(database.as_container().unwrap().child_container("dave").unwrap());
+        dave.set_name(String::from("Dave"));
+        dave.set_age(21);
+        dave.address().unwrap().set_street(String::from("Baker Street"));
+    }
+
+    // Keys must be renamed and children nested
+    let age: u8 = search_database!((&database) /dave::years).unwrap().collect().unwrap();
+    assert_eq!(age, 21);
+    let street: String = search_database!((&database) /dave/address::street).unwrap().collect().unwrap();
+    assert_eq!(street, "Baker Street");
+
+    // Reading the object lazily
+    let mut dave = Person::load_lazy(database.as_container().unwrap().read_container("dave").unwrap());
+    assert_eq!(dave.name().unwrap(), "Dave");
+    assert_eq!(*dave.age().unwrap(), 21);
+    assert_eq!(dave.address().unwrap().street().unwrap(), "Baker Street");
+
+    // Only dirty fields are stored
+    LazyData::new_u8(database.as_container().unwrap().read_container("dave").unwrap().data_writer("years").unwrap(), 30).unwrap();
+    dave.set_name(String::from("David"));
+    dave.store_lazy().unwrap();
+    let age: u8 = search_database!((&database) /dave::years).unwrap().collect().unwrap();
+    assert_eq!(age, 30);
+
+    // Clean fields are reloaded after clearing the cache
+    dave.clear_cache();
+    assert_eq!(*dave.age().unwrap(), 30);
+    assert_eq!(dave.name().unwrap(), "David");
+}
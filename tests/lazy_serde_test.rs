@@ -0,0 +1,71 @@
+#![cfg(feature = "serde")]
+mod isol;
+use isol::*;
+use lazy_db::*;
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum Role {
+    Admin,
+    User(u32),
+    Guest { invited_by: String },
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Config {
+    name: String,
+    port: u16,
+    ratio: f64,
+    enabled: bool,
+    nickname: Option<String>,
+    tags: Vec<String>,
+    limits: BTreeMap<u32, i64>,
+    roles: Vec<Role>,
+    bytes: (u8, char),
+}
+
+#[test]
+fn lazy_serde_round_trip() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("database");
+    let database = LazyDB::init(&path).unwrap();
+    let container = database.as_container().unwrap();
+
+    let og = Config {
+        name: String::from("server"),
+        port: 8080,
+        ratio: 0.75,
+        enabled: true,
+        nickname: None,
+        tags: vec![String::from("a"), String::from("b")],
+        limits: BTreeMap::from([(1, -5), (20, 500)]),
+        roles: vec![Role::Admin, Role::User(7), Role::Guest { invited_by: String::from("Dave") }],
+        bytes: (3, 'x'),
+    };
+
+    // Into the root of a container
+    lazy_serde::to_container(&container, &og).unwrap();
+    let new: Config = lazy_serde::from_container(&container).unwrap();
+    assert_eq!(og, new);
+
+    // Structs become child containers and scalars become `LazyData`
+    let port: u16 = search_database!((&database) port).unwrap().collect().unwrap();
+    assert_eq!(port, 8080);
+    let tag: String = search_database!((&database) /tags::("1")).unwrap().collect().unwrap();
+    assert_eq!(tag, "b");
+
+    // Into a key of a container
+    lazy_serde::to_key(&container, "config", &og).unwrap();
+    let new: Config = lazy_serde::from_key(&container, "config").unwrap();
+    assert_eq!(og, new);
+    lazy_serde::to_key(&container, "number", &42u8).unwrap();
+    assert_eq!(lazy_serde::from_key::<u8>(&container, "number").unwrap(), 42);
+
+    // Map keys can't escape the container or be hidden
+    for key in ["../x", "a/b", "/etc/x", ".hidden", ".."] {
+        let map = BTreeMap::from([(key, 1u8)]);
+        assert!(matches!(lazy_serde::to_key(&container, "map", &map), Err(LDBError::InvalidKey(_))));
+    }
+    assert!(!tmp.get_path().join("x").exists());
+}
@@ -0,0 +1,136 @@
+mod isol;
+use isol::*;
+use lazy_db::*;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// A backend that records the operations it's asked to do and stores everything on the filesystem
+#[derive(Default)]
+struct RecordingBackend {
+    fs: FsBackend,
+    log: Mutex<Vec<String>>,
+}
+
+impl RecordingBackend {
+    fn record(&self, op: &str, path: &Path) {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        self.log.lock().unwrap().push(format!("{op} {name}"));
+    }
+
+    fn recorded(&self, op: &str) -> bool {
+        self.log.lock().unwrap().iter().any(|x| x == op)
+    }
+}
+
+impl StorageBackend for RecordingBackend {
+    fn open_reader(&self, path: &Path) -> Result<FileWrapper, io::Error> {
+        self.record("read", path);
+        self.fs.open_reader(path)
+    }
+
+    fn open_writer(&self, path: &Path) -> Result<FileWrapper, io::Error> {
+        self.record("write", path);
+        self.fs.open_writer(path)
+    }
+
+    fn list(&self, path: &Path) -> Result<Vec<(String, EntryKind)>, io::Error> {
+        self.record("list", path);
+        self.fs.list(path)
+    }
+
+    fn kind(&self, path: &Path) -> Option<EntryKind> {
+        self.fs.kind(path)
+    }
+
+    fn remove(&self, path: &Path) -> Result<(), io::Error> {
+        self.record("remove", path);
+        self.fs.remove(path)
+    }
+
+    fn create_dir(&self, path: &Path) -> Result<(), io::Error> {
+        self.record("mkdir", path);
+        self.fs.create_dir(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), io::Error> {
+        self.record("rename", to);
+        self.fs.rename(from, to)
+    }
+}
+
+#[test]
+fn lazy_storage_backend() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("database");
+    let backend = Arc::new(RecordingBackend::default());
+
+    // Everything goes through the backend
+    let mut database = LazyDB::init_in(backend.clone(), &path).unwrap();
+    write_database!((&database) /nested::data = new_string("Hello world!")).unwrap();
+    write_database!((&database) number = 42u8).unwrap();
+    assert_eq!(search_database!((&database) /nested::data).unwrap().collect_string().unwrap(), "Hello world!");
+    assert_eq!(database.as_container().unwrap().keys().unwrap().collect::<Vec<_>>(), ["nested", "number"]);
+    database.as_container().unwrap().remove("number").unwrap();
+    database.as_container().unwrap().new_container("nested").unwrap();
+    for op in ["write .meta", "mkdir nested", "write data", "read data", "list database", "remove number", "remove nested"] {
+        assert!(backend.recorded(op), "'{op}' wasn't recorded");
+    }
+
+    // Including transactions
+    database.transaction(|tx| tx.write("committed", true)).unwrap();
+    assert!(backend.recorded("rename committed"));
+    assert!(matches!(database.enable_wal(), Err(LDBError::IOError(_))));
+    drop(database);
+
+    // The same data can be loaded from the filesystem directly
+    let database = LazyDB::load_in(backend, &path).unwrap();
+    assert!(search_database!((&database) committed).unwrap().collect_bool().unwrap());
+    let database = LazyDB::load_dir(&path).unwrap();
+    assert!(search_database!((&database) committed).unwrap().collect_bool().unwrap());
+}
+
+#[test]
+fn lazy_storage_in_memory() {
+    let mut database = LazyDB::in_memory().unwrap();
+
+    // The full container API works without a filesystem
+    write_database!((&database) /nested::string = new_string("Hello world!")).unwrap();
+    write_database!((&database) /nested/deeper::array = new_u16_array(&[1, 2, 3])).unwrap();
+    write_database!((&database) number = 42u64).unwrap();
+    write_database!((&database) number = 43u64).unwrap();
+    assert_eq!(search_database!((&database) /nested::string).unwrap().collect_string().unwrap(), "Hello world!");
+    assert_eq!(*search_database!((&database) /nested/deeper::array).unwrap().collect_u16_array().unwrap(), [1, 2, 3]);
+    assert_eq!(search_database!((&database) number).unwrap().collect::<u64>().unwrap(), 43);
+    assert!(matches!(search_database!((&database) missing), Err(LDBError::FileNotFound(_))));
+
+    let container = database.as_container().unwrap();
+    assert_eq!(container.keys().unwrap().collect::<Vec<_>>(), ["nested", "number"]);
+    assert_eq!(container.data_entries().unwrap().collect::<Vec<_>>(), [(String::from("number"), LazyType::U64)]);
+    let mut walked = Vec::new();
+    database.walk(WalkOrder::DepthFirst, |entry| { walked.push(entry.to_string()); Ok(WalkControl::Continue) }).unwrap();
+    assert_eq!(walked, ["/nested", "/nested/deeper", "/nested/deeper::array", "/nested::string", "/::number"]);
+
+    // Unfinished writes are never visible
+    { let mut writer = container.data_writer("unfinished").unwrap(); writer.write(&[0]).unwrap(); }
+    assert!(!container.contains_data("unfinished"));
+
+    // Removing, replacing and transactions
+    container.remove("number").unwrap();
+    assert!(!container.contains_data("number"));
+    assert!(container.new_container("nested").unwrap().is_empty().unwrap());
+    database.transaction(|tx| tx.child_container("staged")?.write("data", 7u8)).unwrap();
+    assert!(database.transaction(|tx| { tx.write("rolled_back", 1u8)?; Err::<(), _>(LDBError::FileNotFound("".into())) }).is_err());
+    assert_eq!(search_database!((&database) /staged::data).unwrap().collect::<u8>().unwrap(), 7);
+    assert!(!database.as_container().unwrap().contains_data("rolled_back"));
+
+    // Compiling to a byte buffer that can be loaded from a file
+    let mut bytes = Vec::new();
+    database.compile_to(&mut bytes).unwrap();
+    let tmp = new_env();
+    let path = tmp.get_path().join("database.ldb");
+    std::fs::write(&path, bytes).unwrap();
+    let database = LazyDB::load_db(&path).unwrap();
+    assert_eq!(search_database!((&database) /staged::data).unwrap().collect::<u8>().unwrap(), 7);
+    assert!(database.as_container().unwrap().read_container("nested").unwrap().is_empty().unwrap()); // empty containers are kept
+}
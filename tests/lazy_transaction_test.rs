@@ -0,0 +1,134 @@
+mod isol;
+use isol::*;
+use lazy_db::*;
+use std::fs::{self, File};
+
+#[test]
+fn lazy_transaction_commit() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("database");
+    let mut database = LazyDB::init(&path).unwrap();
+    write_database!((&database) /users/dave::balance = 100u64).unwrap();
+    write_database!((&database) stale = true).unwrap();
+
+    database.transaction(|tx| {
+        let dave = tx.child_container("users")?.child_container("dave")?;
+        let balance: u64 = dave.read_data("balance")?.collect()?;
+        dave.write("balance", balance - 40)?;
+        tx.child_container("ledger")?.write("0", -40i64)?;
+        tx.remove("stale")?;
+
+        // Reads within the transaction see the staged changes
+        assert_eq!(dave.read_data("balance")?.collect::<u64>()?, 60);
+        assert!(tx.read_data("stale").is_err());
+        Ok(())
+    }).unwrap();
+
+    assert_eq!(search_database!((&database) /users/dave::balance).unwrap().collect::<u64>().unwrap(), 60);
+    assert_eq!(search_database!((&database) /ledger::("0")).unwrap().collect::<i64>().unwrap(), -40);
+    assert!(search_database!((&database) stale).is_err());
+    assert!(!path.join(".txn").exists());
+}
+
+#[test]
+fn lazy_transaction_rollback() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("database");
+    let mut database = LazyDB::init(&path).unwrap();
+    write_database!((&database) balance = 100u64).unwrap();
+
+    // Errors discard the changes
+    let result: Result<(), LDBError> = database.transaction(|tx| {
+        tx.write("balance", 0u64)?;
+        tx.read_data("missing")?;
+        Ok(())
+    });
+    assert!(result.is_err());
+    assert_eq!(search_database!((&database) balance).unwrap().collect::<u64>().unwrap(), 100);
+
+    // Panics discard the changes
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        database.transaction(|tx| {
+            tx.write("balance", 0u64)?;
+            panic!("oh no")
+        }) as Result<(), LDBError>
+    }));
+    assert!(result.is_err());
+    assert_eq!(search_database!((&database) balance).unwrap().collect::<u64>().unwrap(), 100);
+    assert!(!path.join(".txn").exists());
+}
+
+#[test]
+fn lazy_transaction_recover() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("database");
+    let staging = path.join(".txn");
+    write_database!((LazyDB::init(&path).unwrap()) data = 1u8).unwrap();
+
+    // An uncommitted transaction is discarded
+    fs::create_dir(&staging).unwrap();
+    LazyData::new_u8(FileWrapper::new_writer(File::create(staging.join("0")).unwrap()), 2).unwrap();
+    let database = LazyDB::load_dir(&path).unwrap();
+    assert_eq!(search_database!((&database) data).unwrap().collect::<u8>().unwrap(), 1);
+    assert!(!staging.exists());
+
+    // A committed transaction is finished
+    fs::create_dir(&staging).unwrap();
+    LazyData::new_u8(FileWrapper::new_writer(File::create(staging.join("0")).unwrap()), 3).unwrap();
+    LazyData::new_string_array(FileWrapper::new_writer(File::create(staging.join("journal")).unwrap()), &["W", "0", "data"]).unwrap();
+    let database = LazyDB::load_dir(&path).unwrap();
+    assert_eq!(search_database!((&database) data).unwrap().collect::<u8>().unwrap(), 3);
+    assert!(!staging.exists());
+
+    // A malformed journal is reported and moved aside so the database can still be loaded
+    fs::create_dir(&staging).unwrap();
+    LazyData::new_string_array(FileWrapper::new_writer(File::create(staging.join("journal")).unwrap()), &["W", "0"]).unwrap();
+    assert!(matches!(LazyDB::load_dir(&path), Err(LDBError::InvalidValue(..))));
+    assert!(!staging.exists());
+    assert!(path.join(".txn-failed").exists());
+    let database = LazyDB::load_dir(&path).unwrap();
+    assert_eq!(search_database!((&database) data).unwrap().collect::<u8>().unwrap(), 3);
+}
+
+#[test]
+fn lazy_transaction_keys() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("database");
+    let mut database = LazyDB::init(&path).unwrap();
+
+    // Keys with spaces and newlines survive the journal
+    database.transaction(|tx| {
+        tx.child_container("a b")?.write("c\nd", 1u8)?;
+        Ok(())
+    }).unwrap();
+    let container = database.as_container().unwrap().read_container("a b").unwrap();
+    assert_eq!(container.read_data("c\nd").unwrap().collect::<u8>().unwrap(), 1);
+}
+
+#[test]
+fn lazy_transaction_conflict() {
+    let tmp = new_env();
+    let path = tmp.get_path().join("database");
+    let mut database = LazyDB::init(&path).unwrap();
+    write_database!((&database) /container::data = 1u8).unwrap();
+
+    // Writing data over a container fails before anything is applied
+    let result = database.transaction(|tx| {
+        tx.write("other", 2u8)?;
+        tx.write("container", 3u8)?;
+        Ok(())
+    });
+    assert!(result.is_err());
+    assert!(search_database!((&database) other).is_err());
+    assert!(!path.join(".txn").exists());
+
+    // Unless it's removed first
+    database.transaction(|tx| {
+        tx.remove("container")?;
+        tx.write("container", 3u8)?;
+        Ok(())
+    }).unwrap();
+    assert_eq!(search_database!((&database) container).unwrap().collect::<u8>().unwrap(), 3);
+    drop(database);
+    LazyDB::load_dir(&path).unwrap();
+}